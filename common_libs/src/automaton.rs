@@ -0,0 +1,137 @@
+use crate::cycle::{self, Cycle};
+use crate::map::Map;
+use crate::point::{Point, ALL_VECS, ORTHO_VECS};
+use std::hash::Hash;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Neighbourhood {
+    // The 4 orthogonal neighbours.
+    VonNeumann,
+    // The 8 surrounding cells.
+    Moore,
+    Custom(Vec<Point>),
+}
+
+impl Neighbourhood {
+    pub fn offsets(&self) -> &[Point] {
+        match self {
+            Neighbourhood::VonNeumann => &ORTHO_VECS,
+            Neighbourhood::Moore => &ALL_VECS,
+            Neighbourhood::Custom(offsets) => offsets,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Edges {
+    // Neighbours outside of the map are not given to the rule.
+    Bounded,
+    // The map is a torus.
+    Wrapping,
+}
+
+// Applies `rule(cell, neighbours)` to every cell at once, generation after generation.
+pub struct Automaton<T, F> {
+    current: Map<T>,
+    next: Map<T>,
+    neighbourhood: Neighbourhood,
+    edges: Edges,
+    rule: F,
+    generation: usize,
+}
+
+impl<T, F> Automaton<T, F>
+where
+    T: Clone + PartialEq,
+    F: Fn(&T, &[&T]) -> T,
+{
+    pub fn new(map: Map<T>, neighbourhood: Neighbourhood, rule: F) -> Automaton<T, F> {
+        Automaton {
+            next: map.clone(),
+            current: map,
+            neighbourhood,
+            edges: Edges::Bounded,
+            rule,
+            generation: 0,
+        }
+    }
+    pub fn with_edges(mut self, edges: Edges) -> Automaton<T, F> {
+        self.edges = edges;
+        self
+    }
+
+    pub fn map(&self) -> &Map<T> {
+        &self.current
+    }
+    pub fn into_map(self) -> Map<T> {
+        self.current
+    }
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn neighbour(&self, map: &Map<T>, pos: Point) -> Option<Point> {
+        match self.edges {
+            Edges::Bounded => map.is_pos_inside(&pos).then_some(pos),
+            Edges::Wrapping => Some(Point::new(
+                pos.x.rem_euclid(map.width() as isize),
+                pos.y.rem_euclid(map.height() as isize),
+            )),
+        }
+    }
+
+    // Writes the generation following `from` into `to`, which must have the same size.
+    fn compute(&self, from: &Map<T>, to: &mut Map<T>) {
+        let mut neighbours: Vec<&T> = Vec::with_capacity(self.neighbourhood.offsets().len());
+        for (pos, cell) in from.enumerate() {
+            neighbours.clear();
+            neighbours.extend(
+                self.neighbourhood
+                    .offsets()
+                    .iter()
+                    .filter_map(|offset| self.neighbour(from, pos + offset))
+                    .map(|p| &from[p]),
+            );
+            to[pos] = (self.rule)(cell, &neighbours);
+        }
+    }
+
+    // Returns whether any cell changed.
+    pub fn step(&mut self) -> bool {
+        let mut next = std::mem::replace(&mut self.next, Map { map: vec![] });
+        self.compute(&self.current, &mut next);
+        self.next = std::mem::replace(&mut self.current, next);
+        self.generation += 1;
+        self.current != self.next
+    }
+
+    pub fn run(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+
+    // Steps until nothing changes anymore, returns the first generation of the
+    // steady state, or None if it is not reached within `max_generations` steps.
+    pub fn run_until_stable(&mut self, max_generations: usize) -> Option<usize> {
+        for _ in 0..max_generations {
+            if !self.step() {
+                return Some(self.generation - 1);
+            }
+        }
+        None
+    }
+
+    // Cycle of the whole map, starting from the current generation. A steady
+    // state is a cycle of length 1.
+    pub fn find_cycle(&self) -> Cycle
+    where
+        T: Eq + Hash,
+    {
+        cycle::find_cycle(self.current.clone(), |map| {
+            let mut next = map.clone();
+            self.compute(map, &mut next);
+            next
+        })
+    }
+}
@@ -0,0 +1,48 @@
+use std::str::FromStr;
+
+// Minimal command line parsing: `--flag`, `--name value` and `--name=value`.
+#[derive(Debug, Clone, Default)]
+pub struct Args {
+    args: Vec<String>,
+}
+
+impl Args {
+    pub fn from_env() -> Args {
+        Args::new(std::env::args().skip(1))
+    }
+    pub fn new(args: impl IntoIterator<Item = String>) -> Args {
+        Args {
+            args: args.into_iter().collect(),
+        }
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.args.iter().any(|arg| arg == name)
+    }
+
+    pub fn raw_value(&self, name: &str) -> Option<&str> {
+        self.args.iter().enumerate().find_map(|(i, arg)| {
+            match arg.strip_prefix(name)?.strip_prefix('=') {
+                Some(value) => Some(value),
+                None if arg == name => self.args.get(i + 1).map(|s| s.as_str()),
+                None => None,
+            }
+        })
+    }
+
+    // Panics if the value is there but can't be parsed, a typo on the
+    // command line should not silently fall back to a default.
+    pub fn value<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.raw_value(name).map(|value| match value.parse() {
+            Ok(v) => v,
+            Err(_) => panic!("Invalid value for {}: {}", name, value),
+        })
+    }
+    pub fn value_or<T: FromStr>(&self, name: &str, default: T) -> T {
+        self.value(name).unwrap_or(default)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        self.args.iter()
+    }
+}
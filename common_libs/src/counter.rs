@@ -0,0 +1,172 @@
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counter<K: Eq + Hash> {
+    counts: HashMap<K, usize>,
+}
+
+impl<K: Eq + Hash> Default for Counter<K> {
+    fn default() -> Self {
+        Counter {
+            counts: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Eq + Hash> Counter<K> {
+    pub fn new() -> Counter<K> {
+        Self::default()
+    }
+
+    pub fn add(&mut self, key: K, n: usize) {
+        if n > 0 {
+            *self.counts.entry(key).or_default() += n;
+        }
+    }
+    // Removes up to `n` occurrences of `key`, returns how many were actually removed.
+    pub fn remove(&mut self, key: &K, n: usize) -> usize {
+        match self.counts.get_mut(key) {
+            Some(count) if *count > n => {
+                *count -= n;
+                n
+            }
+            Some(_) => self.counts.remove(key).unwrap(),
+            None => 0,
+        }
+    }
+
+    pub fn get(&self, key: &K) -> usize {
+        self.counts.get(key).copied().unwrap_or(0)
+    }
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.counts.contains_key(key)
+    }
+    // Number of distinct keys.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+    // Sum of all the counts.
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, usize)> {
+        self.counts.iter().map(|(k, n)| (k, *n))
+    }
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.counts.keys()
+    }
+    pub fn values(&self) -> impl Iterator<Item = usize> + '_ {
+        self.counts.values().copied()
+    }
+
+    // The `n` keys with the highest counts, highest first.
+    pub fn most_common(&self, n: usize) -> Vec<(&K, usize)> {
+        self.iter()
+            .sorted_by(|(_, a), (_, b)| b.cmp(a))
+            .take(n)
+            .collect()
+    }
+
+    // Every key is replaced by all the keys returned by `f`, each one carrying
+    // the count of the original key.
+    pub fn map_keys_flat<K2, I, F>(&self, mut f: F) -> Counter<K2>
+    where
+        K2: Eq + Hash,
+        I: IntoIterator<Item = K2>,
+        F: FnMut(&K) -> I,
+    {
+        let mut mapped = Counter::new();
+        for (key, n) in self.iter() {
+            for new_key in f(key) {
+                mapped.add(new_key, n);
+            }
+        }
+        mapped
+    }
+}
+
+impl<K: Eq + Hash> FromIterator<K> for Counter<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut counter = Counter::new();
+        counter.extend(iter);
+        counter
+    }
+}
+
+impl<K: Eq + Hash> Extend<K> for Counter<K> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        for key in iter {
+            self.add(key, 1);
+        }
+    }
+}
+
+impl<K: Eq + Hash> IntoIterator for Counter<K> {
+    type Item = (K, usize);
+    type IntoIter = std::collections::hash_map::IntoIter<K, usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.counts.into_iter()
+    }
+}
+
+impl<K: Eq + Hash + Clone> std::ops::AddAssign<&Counter<K>> for Counter<K> {
+    fn add_assign(&mut self, rhs: &Counter<K>) {
+        for (key, n) in rhs.iter() {
+            self.add(key.clone(), n);
+        }
+    }
+}
+
+impl<K: Eq + Hash> std::ops::AddAssign for Counter<K> {
+    fn add_assign(&mut self, rhs: Counter<K>) {
+        for (key, n) in rhs {
+            self.add(key, n);
+        }
+    }
+}
+
+// Counts never go below zero: keys that would do so are dropped.
+impl<K: Eq + Hash> std::ops::SubAssign<&Counter<K>> for Counter<K> {
+    fn sub_assign(&mut self, rhs: &Counter<K>) {
+        for (key, n) in rhs.iter() {
+            self.remove(key, n);
+        }
+    }
+}
+
+impl<K: Eq + Hash> std::ops::SubAssign for Counter<K> {
+    fn sub_assign(&mut self, rhs: Counter<K>) {
+        *self -= &rhs;
+    }
+}
+
+impl<K: Eq + Hash, Rhs> std::ops::Add<Rhs> for Counter<K>
+where
+    Counter<K>: std::ops::AddAssign<Rhs>,
+{
+    type Output = Counter<K>;
+
+    fn add(mut self, rhs: Rhs) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl<K: Eq + Hash, Rhs> std::ops::Sub<Rhs> for Counter<K>
+where
+    Counter<K>: std::ops::SubAssign<Rhs>,
+{
+    type Output = Counter<K>;
+
+    fn sub(mut self, rhs: Rhs) -> Self::Output {
+        self -= rhs;
+        self
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Cycle {
+    pub prefix: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    // Maps any step count to the smallest equivalent one, i.e. one that
+    // reaches the very same state.
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.prefix {
+            n
+        } else {
+            self.prefix + (n - self.prefix) % self.length
+        }
+    }
+}
+
+// Hash-based detection: every visited state is stored, so `step` is called
+// exactly `prefix + length` times.
+pub fn find_cycle<S, F>(initial: S, step: F) -> Cycle
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    find_cycle_with_history(initial, step).0
+}
+
+fn find_cycle_with_history<S, F>(initial: S, mut step: F) -> (Cycle, Vec<S>)
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    let mut seen: HashMap<S, usize> = HashMap::new();
+    let mut history = Vec::new();
+    let mut state = initial;
+    loop {
+        if let Some(&prefix) = seen.get(&state) {
+            let cycle = Cycle {
+                prefix,
+                length: history.len() - prefix,
+            };
+            return (cycle, history);
+        }
+        seen.insert(state.clone(), history.len());
+        let next = step(&state);
+        history.push(state);
+        state = next;
+    }
+}
+
+// Brent's algorithm: only a couple of states are kept alive at any time, at
+// the cost of calling `step` a few more times than the hash-based variant.
+pub fn find_cycle_brent<S, F>(initial: S, mut step: F) -> Cycle
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    let mut prefix = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix += 1;
+    }
+    Cycle { prefix, length }
+}
+
+// Returns the state reached after `n` steps without simulating all of them.
+pub fn nth_state<S, F>(initial: S, step: F, n: usize) -> S
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    let (cycle, mut history) = find_cycle_with_history(initial, step);
+    history.swap_remove(cycle.reduce(n))
+}
+
+// Same as `nth_state`, but memory-free: the cycle is found with Brent's
+// algorithm and the reduced number of steps is simulated again.
+pub fn nth_state_brent<S, F>(initial: S, mut step: F, n: usize) -> S
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    let cycle = find_cycle_brent(initial.clone(), &mut step);
+    (0..cycle.reduce(n)).fold(initial, |state, _| step(&state))
+}
@@ -1,5 +1,5 @@
-use common_libs::point::Point;
-use common_libs::point::{DN_VEC, LX_VEC, RX_VEC, UP_VEC};
+use crate::point::Point;
+use crate::point::{DN_VEC, LX_VEC, RX_VEC, UP_VEC};
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Direction {
@@ -10,6 +10,13 @@ pub enum Direction {
 }
 
 impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
     pub fn to_point(&self) -> Point {
         match self {
             Direction::Up => UP_VEC,
@@ -20,15 +27,35 @@ impl Direction {
     }
 
     pub fn is_vertical(&self) -> bool {
-        match self {
-            Direction::Up | Direction::Down => true,
-            _ => false,
-        }
+        matches!(self, Direction::Up | Direction::Down)
     }
     pub fn is_horizontal(&self) -> bool {
         !self.is_vertical()
     }
 
+    pub fn rotate_cw(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+    pub fn rotate_ccw(&self) -> Direction {
+        self.reverse().rotate_cw()
+    }
+    pub fn reverse(&self) -> Direction {
+        self.rotate_cw().rotate_cw()
+    }
+
+    pub fn to_char(&self) -> char {
+        match self {
+            Direction::Up => '^',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+            Direction::Right => '>',
+        }
+    }
     pub fn from_char(c: char) -> Direction {
         match c {
             '^' => Direction::Up,
@@ -0,0 +1,259 @@
+use crate::map::Map;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::error::{EncodingError, ImageFormatHint, ParameterError, ParameterErrorKind};
+use image::{Delay, ImageError, ImageFormat, ImageResult, RgbImage, Rgba, RgbaImage};
+use std::borrow::Borrow;
+use std::fs::File;
+use std::io::BufWriter;
+use std::iter::Peekable;
+use std::path::Path;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AnimationFormat {
+    Gif,
+    Apng,
+}
+
+impl AnimationFormat {
+    // `.gif` files are GIFs, `.png` and `.apng` ones are animated PNGs.
+    pub fn from_path(path: &Path) -> Option<AnimationFormat> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "gif" => Some(AnimationFormat::Gif),
+            "png" | "apng" => Some(AnimationFormat::Apng),
+            _ => None,
+        }
+    }
+}
+
+const CAPTION_FG: [u8; 3] = [255, 255, 255];
+const CAPTION_BG: [u8; 3] = [0, 0, 0];
+// 3x5 bitmaps for the digits 0-9, one row per byte, most significant bit on the left.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+const CAPTION_HEIGHT: u32 = 7;
+
+pub struct Exporter<F> {
+    palette: F,
+    scale: u32,
+    delay_ms: u32,
+    frame_numbers: bool,
+}
+
+impl<F> Exporter<F> {
+    // `palette` maps every cell to its RGB color.
+    pub fn new(palette: F) -> Exporter<F> {
+        Exporter {
+            palette,
+            scale: 1,
+            delay_ms: 100,
+            frame_numbers: false,
+        }
+    }
+    // Every cell becomes a `scale`x`scale` square.
+    pub fn with_scale(mut self, scale: u32) -> Exporter<F> {
+        self.scale = scale.max(1);
+        self
+    }
+    pub fn with_delay_ms(mut self, delay_ms: u32) -> Exporter<F> {
+        self.delay_ms = delay_ms;
+        self
+    }
+    // Adds a band on top of every frame with its index printed in it.
+    pub fn with_frame_numbers(mut self, frame_numbers: bool) -> Exporter<F> {
+        self.frame_numbers = frame_numbers;
+        self
+    }
+
+    pub fn render<T>(&self, map: &Map<T>, frame_number: usize) -> RgbImage
+    where
+        F: Fn(&T) -> [u8; 3],
+    {
+        let caption_height = if self.frame_numbers {
+            CAPTION_HEIGHT * self.scale
+        } else {
+            0
+        };
+        let (w, h) = (map.width() as u32, map.height() as u32);
+        let mut img = RgbImage::from_pixel(
+            w * self.scale,
+            h * self.scale + caption_height,
+            image::Rgb(CAPTION_BG),
+        );
+        for (pos, cell) in map.enumerate() {
+            let color = image::Rgb((self.palette)(cell));
+            for dy in 0..self.scale {
+                for dx in 0..self.scale {
+                    img.put_pixel(
+                        pos.x as u32 * self.scale + dx,
+                        caption_height + pos.y as u32 * self.scale + dy,
+                        color,
+                    );
+                }
+            }
+        }
+        if self.frame_numbers {
+            self.draw_number(&mut img, frame_number);
+        }
+        img
+    }
+
+    fn draw_number(&self, img: &mut RgbImage, n: usize) {
+        let digits = n.to_string();
+        for (i, digit) in digits.bytes().enumerate() {
+            let bitmap = DIGITS[(digit - b'0') as usize];
+            for (row, bits) in bitmap.iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (0b100 >> col) == 0 {
+                        continue;
+                    }
+                    let x0 = (1 + 4 * i as u32 + col) * self.scale;
+                    let y0 = (1 + row as u32) * self.scale;
+                    for dy in 0..self.scale {
+                        for dx in 0..self.scale {
+                            if x0 + dx < img.width() {
+                                img.put_pixel(x0 + dx, y0 + dy, image::Rgb(CAPTION_FG));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn save_frame<T>(&self, path: impl AsRef<Path>, map: &Map<T>) -> ImageResult<()>
+    where
+        F: Fn(&T) -> [u8; 3],
+    {
+        create_parent_dir(path.as_ref())?;
+        self.render(map, 0).save(path)
+    }
+
+    // The format is picked from the extension of `path`, see `AnimationFormat::from_path`.
+    // Returns the number of frames written, there must be at least one. Frames
+    // are rendered and written one at a time, except for APNGs made from an
+    // iterator that doesn't know its length: the header needs the count.
+    pub fn save_animation<T, I>(&self, path: impl AsRef<Path>, frames: I) -> ImageResult<usize>
+    where
+        F: Fn(&T) -> [u8; 3],
+        I: IntoIterator,
+        I::Item: Borrow<Map<T>>,
+    {
+        let path = path.as_ref();
+        let format = AnimationFormat::from_path(path).ok_or_else(|| {
+            ImageError::Unsupported(ImageFormatHint::PathExtension(path.to_path_buf()).into())
+        })?;
+        self.save_animation_as(path, frames, format)
+    }
+
+    pub fn save_animation_as<T, I>(
+        &self,
+        path: impl AsRef<Path>,
+        frames: I,
+        format: AnimationFormat,
+    ) -> ImageResult<usize>
+    where
+        F: Fn(&T) -> [u8; 3],
+        I: IntoIterator,
+        I::Item: Borrow<Map<T>>,
+    {
+        let path = path.as_ref();
+        let frames = frames.into_iter();
+        let frame_count = match frames.size_hint() {
+            (low, Some(high)) if low == high => Some(low),
+            _ => None,
+        };
+        let mut images = frames
+            .enumerate()
+            .map(|(i, map)| self.render(map.borrow(), i))
+            .peekable();
+        if images.peek().is_none() {
+            return Err(ImageError::Parameter(ParameterError::from_kind(
+                ParameterErrorKind::Generic("No frames to save".to_string()),
+            )));
+        }
+        create_parent_dir(path)?;
+        let file = BufWriter::new(File::create(path)?);
+        match (format, frame_count) {
+            (AnimationFormat::Gif, _) => self.write_gif(file, images),
+            (AnimationFormat::Apng, Some(count)) => self.write_apng(file, images, count),
+            (AnimationFormat::Apng, None) => {
+                let images: Vec<RgbImage> = images.collect();
+                let count = images.len();
+                self.write_apng(file, images.into_iter().peekable(), count)
+            }
+        }
+    }
+
+    fn write_gif(
+        &self,
+        file: BufWriter<File>,
+        images: impl Iterator<Item = RgbImage>,
+    ) -> ImageResult<usize> {
+        let mut encoder = GifEncoder::new_with_speed(file, 30);
+        encoder.set_repeat(Repeat::Infinite)?;
+        let delay = Delay::from_numer_denom_ms(self.delay_ms, 1);
+        let mut count = 0;
+        for img in images {
+            let rgba = RgbaImage::from_fn(img.width(), img.height(), |x, y| {
+                let [r, g, b] = img.get_pixel(x, y).0;
+                Rgba([r, g, b, 255])
+            });
+            encoder.encode_frame(image::Frame::from_parts(rgba, 0, 0, delay))?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    // The APNG header needs the number of frames before the first one.
+    fn write_apng(
+        &self,
+        file: BufWriter<File>,
+        mut images: Peekable<impl Iterator<Item = RgbImage>>,
+        frame_count: usize,
+    ) -> ImageResult<usize> {
+        let Some(first) = images.peek() else {
+            return Ok(0);
+        };
+        let mut encoder = png::Encoder::new(file, first.width(), first.height());
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .set_animated(frame_count as u32, 0)
+            .map_err(png_error)?;
+        encoder
+            .set_frame_delay(self.delay_ms.min(u16::MAX as u32) as u16, 1000)
+            .map_err(png_error)?;
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        let mut count = 0;
+        for img in images {
+            writer.write_image_data(img.as_raw()).map_err(png_error)?;
+            count += 1;
+        }
+        writer.finish().map_err(png_error)?;
+        Ok(count)
+    }
+}
+
+fn create_parent_dir(path: &Path) -> std::io::Result<()> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => std::fs::create_dir_all(dir),
+        _ => Ok(()),
+    }
+}
+
+fn png_error(e: png::EncodingError) -> ImageError {
+    ImageError::Encoding(EncodingError::new(
+        ImageFormatHint::Exact(ImageFormat::Png),
+        e,
+    ))
+}
@@ -0,0 +1,353 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Display;
+use std::hash::Hash;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TopoError<N> {
+    // Nodes of a strongly connected component with a cycle, in insertion order.
+    Cycle(Vec<N>),
+    // Two nodes that are both free to go next: the order is not unique.
+    Ambiguous(N, N),
+}
+
+// Directed graph without parallel edges. Nodes are listed in insertion order,
+// the neighbours of a node in the order their edges were added.
+#[derive(Debug, Clone)]
+pub struct DiGraph<N> {
+    nodes: Vec<N>,
+    index: HashMap<N, usize>,
+    successors: Vec<Vec<usize>>,
+    predecessors: Vec<Vec<usize>>,
+    edges: HashSet<(usize, usize)>,
+}
+
+impl<N> Default for DiGraph<N> {
+    fn default() -> Self {
+        DiGraph {
+            nodes: Vec::new(),
+            index: HashMap::new(),
+            successors: Vec::new(),
+            predecessors: Vec::new(),
+            edges: HashSet::new(),
+        }
+    }
+}
+
+impl<N> DiGraph<N>
+where
+    N: Hash + Eq + Clone,
+{
+    pub fn new() -> DiGraph<N> {
+        DiGraph::default()
+    }
+    pub fn from_edges(edges: impl IntoIterator<Item = (N, N)>) -> DiGraph<N> {
+        let mut graph = DiGraph::new();
+        for (from, to) in edges {
+            graph.add_edge(from, to);
+        }
+        graph
+    }
+
+    // Returns the node index, adding it if needed.
+    fn add(&mut self, node: N) -> usize {
+        if let Some(i) = self.index.get(&node) {
+            return *i;
+        }
+        self.nodes.push(node.clone());
+        self.successors.push(Vec::new());
+        self.predecessors.push(Vec::new());
+        self.index.insert(node, self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+    pub fn add_node(&mut self, node: N) {
+        self.add(node);
+    }
+    // Returns false if the edge was already there.
+    pub fn add_edge(&mut self, from: N, to: N) -> bool {
+        let (from, to) = (self.add(from), self.add(to));
+        if !self.edges.insert((from, to)) {
+            return false;
+        }
+        self.successors[from].push(to);
+        self.predecessors[to].push(from);
+        true
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+    pub fn contains_node(&self, node: &N) -> bool {
+        self.index.contains_key(node)
+    }
+    pub fn contains_edge(&self, from: &N, to: &N) -> bool {
+        match (self.index.get(from), self.index.get(to)) {
+            (Some(f), Some(t)) => self.edges.contains(&(*f, *t)),
+            _ => false,
+        }
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.nodes.iter()
+    }
+    pub fn edges(&self) -> impl Iterator<Item = (&N, &N)> {
+        self.successors
+            .iter()
+            .enumerate()
+            .flat_map(move |(f, tos)| tos.iter().map(move |t| (&self.nodes[f], &self.nodes[*t])))
+    }
+    // Empty for unknown nodes.
+    pub fn successors(&self, node: &N) -> impl Iterator<Item = &N> {
+        self.neighbours(node, &self.successors)
+    }
+    pub fn predecessors(&self, node: &N) -> impl Iterator<Item = &N> {
+        self.neighbours(node, &self.predecessors)
+    }
+    fn neighbours<'a>(
+        &'a self,
+        node: &N,
+        adjacency: &'a [Vec<usize>],
+    ) -> impl Iterator<Item = &'a N> {
+        self.index
+            .get(node)
+            .into_iter()
+            .flat_map(move |i| adjacency[*i].iter().map(|j| &self.nodes[*j]))
+    }
+
+    // Graph made of the given nodes, in that order, and of the edges between them.
+    pub fn subgraph<'a>(&self, nodes: impl IntoIterator<Item = &'a N>) -> DiGraph<N>
+    where
+        N: 'a,
+    {
+        let mut graph = DiGraph::new();
+        let kept: Vec<usize> = nodes
+            .into_iter()
+            .filter_map(|n| {
+                graph.add_node(n.clone());
+                self.index.get(n).copied()
+            })
+            .collect();
+        let kept_set: HashSet<usize> = kept.iter().copied().collect();
+        for from in kept {
+            for to in self.successors[from]
+                .iter()
+                .filter(|t| kept_set.contains(t))
+            {
+                graph.add_edge(self.nodes[from].clone(), self.nodes[*to].clone());
+            }
+        }
+        graph
+    }
+
+    fn reachable_indices(&self, start: usize) -> Vec<bool> {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut queue: VecDeque<usize> = self.successors[start].iter().copied().collect();
+        while let Some(i) = queue.pop_front() {
+            if !seen[i] {
+                seen[i] = true;
+                queue.extend(self.successors[i].iter().filter(|j| !seen[**j]));
+            }
+        }
+        seen
+    }
+
+    // Nodes at the end of a path of at least one edge from `node`. The node
+    // itself is only there if it is on a cycle.
+    pub fn reachable_from(&self, node: &N) -> Vec<&N> {
+        let Some(start) = self.index.get(node) else {
+            return Vec::new();
+        };
+        let seen = self.reachable_indices(*start);
+        (0..self.nodes.len())
+            .filter(|i| seen[*i])
+            .map(|i| &self.nodes[i])
+            .collect()
+    }
+    pub fn is_reachable(&self, from: &N, to: &N) -> bool {
+        match (self.index.get(from), self.index.get(to)) {
+            (Some(f), Some(t)) => self.reachable_indices(*f)[*t],
+            _ => false,
+        }
+    }
+
+    // Kahn's algorithm, ties are broken by insertion order.
+    pub fn topological_sort(&self) -> Result<Vec<N>, TopoError<N>> {
+        self.kahn(false)
+    }
+    // Fails as soon as two nodes could go next.
+    pub fn unique_topological_sort(&self) -> Result<Vec<N>, TopoError<N>> {
+        self.kahn(true)
+    }
+
+    fn kahn(&self, unique: bool) -> Result<Vec<N>, TopoError<N>> {
+        let mut in_degree: Vec<usize> = self.predecessors.iter().map(Vec::len).collect();
+        let mut ready: VecDeque<usize> = (0..self.nodes.len())
+            .filter(|i| in_degree[*i] == 0)
+            .collect();
+        let mut sorted = Vec::with_capacity(self.nodes.len());
+        while let Some(i) = ready.pop_front() {
+            if let (true, Some(other)) = (unique, ready.front()) {
+                let (a, b) = (i.min(*other), i.max(*other));
+                return Err(TopoError::Ambiguous(
+                    self.nodes[a].clone(),
+                    self.nodes[b].clone(),
+                ));
+            }
+            sorted.push(self.nodes[i].clone());
+            for j in &self.successors[i] {
+                in_degree[*j] -= 1;
+                if in_degree[*j] == 0 {
+                    ready.push_back(*j);
+                }
+            }
+        }
+        if sorted.len() < self.nodes.len() {
+            return Err(TopoError::Cycle(self.cycles().swap_remove(0)));
+        }
+        Ok(sorted)
+    }
+
+    // Tarjan's algorithm, without recursion. Components come out in reverse
+    // topological order, their nodes in insertion order.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<N>> {
+        const UNVISITED: usize = usize::MAX;
+        let n = self.nodes.len();
+        let mut order = vec![UNVISITED; n];
+        let mut low_link = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        let mut counter = 0;
+        for root in 0..n {
+            if order[root] != UNVISITED {
+                continue;
+            }
+            // (node, index of the next successor to look at)
+            let mut calls = vec![(root, 0)];
+            while let Some((i, next)) = calls.pop() {
+                if next == 0 {
+                    order[i] = counter;
+                    low_link[i] = counter;
+                    counter += 1;
+                    stack.push(i);
+                    on_stack[i] = true;
+                }
+                if let Some(&j) = self.successors[i].get(next) {
+                    calls.push((i, next + 1));
+                    if order[j] == UNVISITED {
+                        calls.push((j, 0));
+                    } else if on_stack[j] {
+                        low_link[i] = low_link[i].min(order[j]);
+                    }
+                    continue;
+                }
+                if low_link[i] == order[i] {
+                    let mut component = Vec::new();
+                    loop {
+                        let j = stack.pop().unwrap();
+                        on_stack[j] = false;
+                        component.push(j);
+                        if j == i {
+                            break;
+                        }
+                    }
+                    component.sort();
+                    components.push(
+                        component
+                            .into_iter()
+                            .map(|j| self.nodes[j].clone())
+                            .collect(),
+                    );
+                }
+                if let Some((parent, _)) = calls.last() {
+                    low_link[*parent] = low_link[*parent].min(low_link[i]);
+                }
+            }
+        }
+        components
+    }
+
+    // Components with at least one cycle: more than one node, or a self loop.
+    pub fn cycles(&self) -> Vec<Vec<N>> {
+        self.strongly_connected_components()
+            .into_iter()
+            .filter(|c| c.len() > 1 || self.contains_edge(&c[0], &c[0]))
+            .collect()
+    }
+    pub fn is_acyclic(&self) -> bool {
+        self.cycles().is_empty()
+    }
+
+    // Same nodes, with an edge wherever there is a path.
+    pub fn transitive_closure(&self) -> DiGraph<N> {
+        let mut closure = self.subgraph(&self.nodes);
+        for from in 0..self.nodes.len() {
+            let seen = self.reachable_indices(from);
+            for to in (0..self.nodes.len()).filter(|t| seen[*t]) {
+                closure.add_edge(self.nodes[from].clone(), self.nodes[to].clone());
+            }
+        }
+        closure
+    }
+
+    // Fewest edges with the same reachability. Only unique for graphs
+    // without cycles, so those are refused.
+    pub fn transitive_reduction(&self) -> Result<DiGraph<N>, TopoError<N>> {
+        if let Some(cycle) = self.cycles().into_iter().next() {
+            return Err(TopoError::Cycle(cycle));
+        }
+        let reachable: Vec<Vec<bool>> = (0..self.nodes.len())
+            .map(|i| self.reachable_indices(i))
+            .collect();
+        let mut reduction = DiGraph::new();
+        for node in &self.nodes {
+            reduction.add_node(node.clone());
+        }
+        for (from, tos) in self.successors.iter().enumerate() {
+            for to in tos {
+                // Redundant if another successor already leads there.
+                let redundant = tos
+                    .iter()
+                    .any(|other| other != to && reachable[*other][*to]);
+                if !redundant {
+                    reduction.add_edge(self.nodes[from].clone(), self.nodes[*to].clone());
+                }
+            }
+        }
+        Ok(reduction)
+    }
+
+    // Graphviz source, `dot -Tsvg` turns it into a picture. Labels are escaped,
+    // so they can hold quotes and backslashes.
+    pub fn to_dot(&self, name: &str) -> String
+    where
+        N: Display,
+    {
+        let mut dot = format!("digraph \"{}\" {{\n", dot_escape(name));
+        for (i, node) in self.nodes.iter().enumerate() {
+            if self.successors[i].is_empty() && self.predecessors[i].is_empty() {
+                dot += &format!("    \"{}\";\n", dot_escape(node));
+            }
+        }
+        for (from, to) in self.edges() {
+            dot += &format!("    \"{}\" -> \"{}\";\n", dot_escape(from), dot_escape(to));
+        }
+        dot += "}\n";
+        dot
+    }
+}
+
+// Backslash before `"` and `\`, the only characters a quoted DOT id can't
+// hold as they are.
+fn dot_escape(label: impl Display) -> String {
+    let mut escaped = String::new();
+    for c in label.to_string().chars() {
+        if c == '"' || c == '\\' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
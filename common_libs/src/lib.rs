@@ -1,6 +1,25 @@
+pub mod automaton;
+pub mod cli;
+pub mod counter;
+pub mod cycle;
+pub mod direction;
+#[cfg(feature = "export")]
+pub mod export;
+pub mod graph;
 pub mod lineq;
 pub mod map;
+pub mod memo;
+pub mod parallel;
+pub mod pattern;
 pub mod point;
+pub mod rect;
+pub mod sparse_map;
+pub mod viz;
+pub mod walker;
+pub mod word_search;
+pub mod wrapping_map;
+
+pub use counter::Counter;
 
 pub fn add(left: u64, right: u64) -> u64 {
     left + right
@@ -9,6 +28,7 @@ pub fn add(left: u64, right: u64) -> u64 {
 #[cfg(test)]
 mod tests {
     use gcd::Gcd;
+    use itertools::Itertools;
 
     use super::*;
 
@@ -80,4 +100,889 @@ mod tests {
         *p *= -2;
         assert_eq!(Point::new(-12, -20), *p);
     }
+    #[test]
+    fn cycle_detection() {
+        use cycle::Cycle;
+        // 3 -> 10 -> 5 -> 16 -> 8 -> 4 -> 2 -> 1 -> 4 -> ...
+        let collatz = |n: &u64| {
+            if n.is_multiple_of(2) {
+                n / 2
+            } else {
+                3 * n + 1
+            }
+        };
+        let expected = Cycle {
+            prefix: 5,
+            length: 3,
+        };
+        assert_eq!(expected, cycle::find_cycle(3, collatz));
+        assert_eq!(expected, cycle::find_cycle_brent(3, collatz));
+
+        let pure_cycle = |n: &u64| (n + 1) % 7;
+        let expected = Cycle {
+            prefix: 0,
+            length: 7,
+        };
+        assert_eq!(expected, cycle::find_cycle(0, pure_cycle));
+        assert_eq!(expected, cycle::find_cycle_brent(0, pure_cycle));
+
+        let fixed_point = Cycle {
+            prefix: 1,
+            length: 1,
+        };
+        assert_eq!(fixed_point, cycle::find_cycle(5, |_: &u64| 0));
+        assert_eq!(fixed_point, cycle::find_cycle_brent(5, |_: &u64| 0));
+    }
+    #[test]
+    fn cycle_nth_state() {
+        let collatz = |n: &u64| {
+            if n.is_multiple_of(2) {
+                n / 2
+            } else {
+                3 * n + 1
+            }
+        };
+        assert_eq!(3, cycle::nth_state(3, collatz, 0));
+        assert_eq!(8, cycle::nth_state(3, collatz, 4));
+        assert_eq!(4, cycle::nth_state(3, collatz, 5));
+        assert_eq!(1, cycle::nth_state(3, collatz, 7));
+        assert_eq!(4, cycle::nth_state(3, collatz, 1_000_000_000_001));
+        assert_eq!(1, cycle::nth_state_brent(3, collatz, 1_000_000_000_000));
+        assert_eq!(
+            cycle::nth_state(17, collatz, 123_456_789),
+            cycle::nth_state_brent(17, collatz, 123_456_789)
+        );
+    }
+    fn fibonacci(n: u64, memo: &mut memo::Memo<u64, u64>) -> u64 {
+        memo.get_or_compute(n, |memo, &n| match n {
+            0 | 1 => n,
+            _ => fibonacci(n - 1, memo) + fibonacci(n - 2, memo),
+        })
+    }
+    #[test]
+    fn memo_recursive() {
+        use memo::{Memo, MemoStats};
+        let mut memo = Memo::new();
+        assert_eq!(12586269025, fibonacci(50, &mut memo));
+        assert_eq!(51, memo.len());
+        assert_eq!(
+            MemoStats {
+                hits: 48,
+                misses: 51,
+                evictions: 0
+            },
+            memo.stats()
+        );
+        assert_eq!(55, fibonacci(10, &mut memo));
+        assert_eq!(49, memo.stats().hits);
+    }
+    #[test]
+    fn memo_bounded() {
+        use memo::Memo;
+        let mut memo = Memo::with_bound(3);
+        assert_eq!(12586269025, fibonacci(50, &mut memo));
+        assert_eq!(3, memo.len());
+        assert_eq!(48, memo.stats().evictions);
+        assert_eq!(Some(&12586269025), memo.get(&50));
+        assert_eq!(None, memo.get(&0));
+    }
+    #[test]
+    fn counter_basics() {
+        let mut counter: Counter<char> = "abracadabra".chars().collect();
+        assert_eq!(5, counter.get(&'a'));
+        assert_eq!(0, counter.get(&'z'));
+        assert_eq!(5, counter.len());
+        assert_eq!(11, counter.total());
+        assert_eq!(vec![(&'a', 5)], counter.most_common(1));
+        counter.add('z', 3);
+        counter.add('y', 0);
+        assert_eq!(3, counter.get(&'z'));
+        assert!(!counter.contains_key(&'y'));
+        assert_eq!(2, counter.remove(&'b', 7));
+        assert!(!counter.contains_key(&'b'));
+    }
+    #[test]
+    fn counter_arithmetic() {
+        let a: Counter<char> = "aaabc".chars().collect();
+        let b: Counter<char> = "abbd".chars().collect();
+        let sum = a.clone() + &b;
+        assert_eq!(Counter::from_iter("aaaabbbcd".chars()), sum);
+        let diff = a - b;
+        assert_eq!(Counter::from_iter("aac".chars()), diff);
+    }
+    #[test]
+    fn counter_map_keys_flat() {
+        let counter: Counter<u64> = [10, 10, 3, 22].into_iter().collect();
+        let digits = counter.map_keys_flat(|n| n.to_string().chars().collect::<Vec<_>>());
+        assert_eq!(Counter::from_iter("1010322".chars()), digits);
+    }
+    #[test]
+    fn viz_headless_dump() {
+        use map::Map;
+        use point::Point;
+        use viz::{Color, Frame, PlayMode, Player};
+        let map = Map::from_str("#..\n.#.");
+        let frames = (0..3).map(|x| {
+            Frame::from_map(&map)
+                .overlay(&Point::from_usize(x, 1), '@', Color::Red)
+                .with_caption(format!("t={}", x))
+        });
+        let mut player = Player::new(Vec::new(), Box::new(std::io::empty()), PlayMode::Headless);
+        player.play(frames).unwrap();
+        assert_eq!(3, player.frames_shown());
+        let dump = String::from_utf8(player.into_inner()).unwrap();
+        assert_eq!("t=0\n#..\n@#.\n\nt=1\n#..\n.@.\n\nt=2\n#..\n.#@\n\n", dump);
+    }
+    #[test]
+    fn viz_step_through() {
+        use map::Map;
+        use viz::{Color, Frame, PlayMode, Player};
+        let frame = Frame::from_map(&Map::from_str("ab"))
+            .overlay(&point::Point::new(1, 0), 'b', Color::Green)
+            .overlay(&point::Point::new(5, 5), 'x', Color::Red);
+        assert_eq!("a\x1b[32mb\x1b[0m", frame.to_ansi());
+        let input = std::io::Cursor::new("\nq\n");
+        let mut player = Player::new(Vec::new(), Box::new(input), PlayMode::Step);
+        player.play(std::iter::repeat_n(frame, 5)).unwrap();
+        assert_eq!(2, player.frames_shown());
+    }
+    #[test]
+    fn viz_play_mode_from_args() {
+        use viz::PlayMode;
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+        assert_eq!(
+            Ok(None),
+            PlayMode::from_args(args("day06 --foo").into_iter())
+        );
+        assert_eq!(
+            Ok(Some(PlayMode::Animate { fps: 30.0 })),
+            PlayMode::from_args(args("day06 --animate").into_iter())
+        );
+        assert_eq!(
+            Ok(Some(PlayMode::Animate { fps: 2.5 })),
+            PlayMode::from_args(args("day06 --animate=2.5").into_iter())
+        );
+        assert_eq!(
+            Ok(Some(PlayMode::Step)),
+            PlayMode::from_args(args("day06 --step").into_iter())
+        );
+        assert_eq!(
+            Ok(Some(PlayMode::Headless)),
+            PlayMode::from_args(args("day06 --dump").into_iter())
+        );
+        for fps in ["0", "-2", "inf", "NaN", "fast"] {
+            let arg = format!("day06 --animate={}", fps);
+            assert!(
+                PlayMode::from_args(args(&arg).into_iter()).is_err(),
+                "{}",
+                fps
+            );
+        }
+    }
+    #[cfg(feature = "export")]
+    fn export_test_frames() -> Vec<map::Map<bool>> {
+        (0..4)
+            .map(|t| {
+                let mut m = map::Map::from_size_value(5, 3, false);
+                m[point::Point::new(t, 1)] = true;
+                m
+            })
+            .collect()
+    }
+    #[cfg(feature = "export")]
+    fn export_palette(cell: &bool) -> [u8; 3] {
+        if *cell {
+            [0, 255, 0]
+        } else {
+            [0, 0, 0]
+        }
+    }
+    #[test]
+    #[cfg(feature = "export")]
+    fn export_render() {
+        use export::Exporter;
+        let frames = export_test_frames();
+        let img = Exporter::new(export_palette)
+            .with_scale(2)
+            .render(&frames[1], 1);
+        assert_eq!((10, 6), img.dimensions());
+        assert_eq!([0, 255, 0], img.get_pixel(3, 3).0);
+        assert_eq!([0, 0, 0], img.get_pixel(1, 3).0);
+
+        let img = Exporter::new(export_palette)
+            .with_frame_numbers(true)
+            .render(&frames[1], 1);
+        assert_eq!((5, 10), img.dimensions());
+        // top of the "1" digit
+        assert_eq!([255, 255, 255], img.get_pixel(2, 1).0);
+        assert_eq!([0, 255, 0], img.get_pixel(1, 8).0);
+    }
+    #[test]
+    #[cfg(feature = "export")]
+    fn export_animations() {
+        use export::Exporter;
+        use image::AnimationDecoder;
+        let dir = std::env::temp_dir().join(format!("common_libs_export_{}", std::process::id()));
+        let exporter = Exporter::new(export_palette)
+            .with_scale(3)
+            .with_frame_numbers(true);
+
+        let gif_path = dir.join("nested/anim.gif");
+        assert_eq!(
+            4,
+            exporter
+                .save_animation(&gif_path, export_test_frames())
+                .unwrap()
+        );
+        let file = std::io::BufReader::new(std::fs::File::open(&gif_path).unwrap());
+        let decoder = image::codecs::gif::GifDecoder::new(file).unwrap();
+        let frames = decoder.into_frames().collect_frames().unwrap();
+        assert_eq!(4, frames.len());
+        assert_eq!((15, 30), frames[0].buffer().dimensions());
+
+        let apng_path = dir.join("anim.apng");
+        let maps = export_test_frames();
+        assert_eq!(4, exporter.save_animation(&apng_path, &maps).unwrap());
+        let reader = png::Decoder::new(std::io::BufReader::new(
+            std::fs::File::open(&apng_path).unwrap(),
+        ))
+        .read_info()
+        .unwrap();
+        assert_eq!(4, reader.info().animation_control.unwrap().num_frames);
+        // Frames made on the fly are not collected first.
+        let lazy = (0..3).map(|i| maps[i].clone());
+        assert_eq!(3, exporter.save_animation(&apng_path, lazy).unwrap());
+        let reader = png::Decoder::new(std::io::BufReader::new(
+            std::fs::File::open(&apng_path).unwrap(),
+        ))
+        .read_info()
+        .unwrap();
+        assert_eq!(3, reader.info().animation_control.unwrap().num_frames);
+        // Without a known length the frames are collected first.
+        let unsized_frames = maps.iter().filter(|m| m[point::Point::new(0, 1)]);
+        assert_eq!(
+            1,
+            exporter.save_animation(&apng_path, unsized_frames).unwrap()
+        );
+        let reader = png::Decoder::new(std::io::BufReader::new(
+            std::fs::File::open(&apng_path).unwrap(),
+        ))
+        .read_info()
+        .unwrap();
+        assert_eq!(1, reader.info().animation_control.unwrap().num_frames);
+        // Nothing is written without frames.
+        let empty_path = dir.join("empty.png");
+        assert!(exporter
+            .save_animation(&empty_path, Vec::<map::Map<bool>>::new())
+            .is_err());
+        assert!(!empty_path.exists());
+
+        assert!(exporter
+            .save_animation(dir.join("anim.bmp"), &maps)
+            .is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    #[test]
+    fn cli_args() {
+        use cli::Args;
+        let args = Args::new(
+            "--width 11 --height=7 --verbose --mode bounce"
+                .split_whitespace()
+                .map(String::from),
+        );
+        assert!(args.flag("--verbose"));
+        assert!(!args.flag("--quiet"));
+        assert_eq!(Some(11), args.value::<isize>("--width"));
+        assert_eq!(Some(7), args.value::<isize>("--height"));
+        assert_eq!(Some("bounce"), args.raw_value("--mode"));
+        assert_eq!(100, args.value_or("--ticks", 100));
+        assert_eq!(None, args.raw_value("--wid"));
+    }
+    #[test]
+    fn wrapping_map_torus() {
+        use point::Point;
+        use wrapping_map::WrappingMap;
+        let mut map = WrappingMap::new(map::Map::from_str("abc\ndef"));
+        assert_eq!('a', map[Point::new(3, 2)]);
+        assert_eq!('f', map[Point::new(-1, -1)]);
+        assert_eq!(Some(&'e'), map.get(&Point::new(-5, 7)));
+        map[Point::new(-3, 0)] = 'z';
+        assert_eq!('z', map.map[Point::new(0, 0)]);
+        let mut neighbours: Vec<Point> = map.ortho_neighbours(&Point::new(0, 0)).collect();
+        neighbours.sort_by_key(|p| (p.y, p.x));
+        assert_eq!(
+            vec![
+                Point::new(1, 0),
+                Point::new(2, 0),
+                Point::new(0, 1),
+                Point::new(0, 1)
+            ],
+            neighbours
+        );
+    }
+    #[test]
+    fn wrapping_map_cylinder() {
+        use point::Point;
+        use wrapping_map::{WrapAxes, WrappingMap};
+        let map = WrappingMap::with_axes(map::Map::from_str("abc\ndef"), WrapAxes::X);
+        assert_eq!('c', map[Point::new(-1, 0)]);
+        assert!(map.is_pos_inside(&Point::new(100, 1)));
+        assert!(!map.is_pos_inside(&Point::new(0, 2)));
+        assert_eq!(None, map.get(&Point::new(0, -1)));
+        assert_eq!(3, map.ortho_neighbours(&Point::new(0, 0)).count());
+
+        let map = WrappingMap::with_axes(map::Map::from_str("abc\ndef"), WrapAxes::Y);
+        assert_eq!('d', map[Point::new(0, -1)]);
+        assert_eq!(None, map.get(&Point::new(3, 0)));
+    }
+    #[test]
+    #[should_panic]
+    fn wrapping_map_cylinder_out_of_bounds() {
+        use wrapping_map::{WrapAxes, WrappingMap};
+        let map = WrappingMap::with_axes(map::Map::from_str("abc\ndef"), WrapAxes::X);
+        let _ = map[point::Point::new(0, 2)];
+    }
+    #[test]
+    fn rect_bounds() {
+        use point::Point;
+        use rect::Rect;
+        let r = Rect::from_corners(&Point::new(3, -1), &Point::new(1, 2));
+        assert_eq!(Rect::new(Point::new(1, -1), 3, 4), r);
+        assert_eq!(Point::new(3, 2), r.end());
+        assert!(r.contains(&Point::new(2, 0)) && !r.contains(&Point::new(4, 0)));
+        assert_eq!(
+            Rect::new(Point::new(-2, -1), 6, 4),
+            r.expanded_to(&Point::new(-2, 0))
+        );
+        assert_eq!(
+            vec![
+                Point::new(0, 0),
+                Point::new(1, 0),
+                Point::new(0, 1),
+                Point::new(1, 1)
+            ],
+            Rect::new(Point::new(0, 0), 2, 2)
+                .positions()
+                .collect::<Vec<_>>()
+        );
+    }
+    #[test]
+    fn sparse_map() {
+        use point::Point;
+        use rect::Rect;
+        use sparse_map::SparseMap;
+        let mut sparse = SparseMap::new('.');
+        assert_eq!(None, sparse.bounds());
+        assert_eq!(None, sparse.to_map());
+        assert_eq!('.', sparse[Point::new(-100, 100)]);
+        sparse.insert(Point::new(-2, 1), '#');
+        sparse[Point::new(1, -1)] = '#';
+        sparse[Point::new(0, 0)] = 'o';
+        assert_eq!(Some(Rect::new(Point::new(-2, -1), 4, 3)), sparse.bounds());
+        assert_eq!("...#\n..o.\n#...", sparse.to_string());
+        let mut hashes: Vec<Point> = sparse.find_all_positions(&'#').collect();
+        hashes.sort_by_key(|p| p.x);
+        assert_eq!(vec![Point::new(-2, 1), Point::new(1, -1)], hashes);
+        assert_eq!(
+            2,
+            sparse
+                .ortho_neighbours(&Point::new(0, -1))
+                .filter(|(_, c)| **c != '.')
+                .count()
+        );
+
+        sparse.remove(&Point::new(-2, 1));
+        assert_eq!(Some(Rect::new(Point::new(0, -1), 2, 2)), sparse.bounds());
+        let dense = sparse.to_map().unwrap();
+        assert_eq!(".#\no.", dense.dump());
+        let back = SparseMap::from_map(&dense, '.');
+        assert_eq!(2, back.len());
+        assert_eq!('o', back[Point::new(0, 1)]);
+    }
+    #[test]
+    fn map_transforms() {
+        use map::Map;
+        let m = Map::from_str("abc\ndef");
+        assert_eq!("ad\nbe\ncf", m.transpose().dump());
+        assert_eq!("da\neb\nfc", m.rotate_cw().dump());
+        assert_eq!("cf\nbe\nad", m.rotate_ccw().dump());
+        assert_eq!("cba\nfed", m.flip_h().dump());
+        assert_eq!("def\nabc", m.flip_v().dump());
+        assert_eq!(m, m.rotate_cw().rotate_ccw());
+        assert_eq!(m.rotate_cw().rotate_cw(), m.flip_h().flip_v());
+
+        let symmetries: Vec<Map> = m.symmetries().collect();
+        assert_eq!(8, symmetries.len());
+        assert_eq!(m, symmetries[0]);
+        assert!(symmetries.iter().all_unique());
+        assert!(symmetries.contains(&m.transpose()));
+        let square = Map::from_str("ab\nba");
+        assert_eq!(2, square.symmetries().unique().count());
+    }
+    #[test]
+    fn map_view() {
+        use map::{Map, Rect};
+        use point::Point;
+        let m = Map::from_str("abcd\nefgh\nijkl");
+        let view = m.view(Rect::new(Point::new(1, 1), 2, 2));
+        assert_eq!((2, 2), (view.width(), view.height()));
+        assert_eq!('f', view[Point::new(0, 0)]);
+        assert_eq!('k', view[Point::new(1, 1)]);
+        assert_eq!(None, view.get(&Point::new(2, 0)));
+        assert_eq!(vec!['f', 'g'], view.iter_rows().next().unwrap().to_vec());
+        assert_eq!("fg\njk", view.to_map().dump());
+        assert_eq!(
+            vec![(Point::new(1, 0), &'g')],
+            view.enumerate()
+                .filter(|(_, c)| **c == 'g')
+                .collect::<Vec<_>>()
+        );
+        let whole = m.view(Rect::new(Point::new(0, 0), m.width(), m.height()));
+        assert!(whole.enumerate().eq(m.enumerate()));
+    }
+    #[test]
+    #[should_panic]
+    fn map_view_out_of_bounds() {
+        use map::{Map, Rect};
+        Map::from_str("ab\ncd").view(Rect::new(point::Point::new(1, 1), 2, 1));
+    }
+    #[test]
+    fn map_lines() {
+        use map::Map;
+        use point::Point;
+        let m = Map::from_str("abc\ndef");
+        let to_strings = |lines: Vec<map::MapLine<char>>| {
+            lines
+                .into_iter()
+                .map(|l| l.map(|(_, c)| *c).collect::<String>())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["abc", "def"], to_strings(m.rows().collect()));
+        assert_eq!(vec!["ad", "be", "cf"], to_strings(m.columns().collect()));
+        assert_eq!(
+            vec!["ae", "bf", "c", "d"],
+            to_strings(m.diagonals().collect())
+        );
+        assert_eq!(
+            vec!["a", "bd", "ce", "f"],
+            to_strings(m.anti_diagonals().collect())
+        );
+        assert_eq!(
+            vec!["c", "d", "e", "f", "g", "ha", "ib"],
+            to_strings(
+                Map::from_str("abc\ndef\nghi")
+                    .lines_in(Point::new(-1, -2))
+                    .collect()
+            )
+        );
+        assert_eq!(
+            vec!["a", "db", "ec", "f"],
+            to_strings(m.lines_in(Point::new(1, -1)).collect())
+        );
+        assert_eq!(
+            vec!["cba", "fed"],
+            m.rows()
+                .map(|l| l.rev().map(|(_, c)| *c).collect::<String>())
+                .collect::<Vec<_>>()
+        );
+        let column = m.columns().nth(2).unwrap();
+        assert_eq!(2, column.len());
+        assert_eq!(
+            vec![(Point::new(2, 0), &'c'), (Point::new(2, 1), &'f')],
+            column.collect::<Vec<_>>()
+        );
+    }
+    #[test]
+    fn word_search_multiple_words() {
+        use map::Map;
+        use point::{Point, ALL_VECS, RX_VEC};
+        use word_search::WordSearch;
+        let search = WordSearch::new(["he", "she", "his", "hers"]);
+        let m = Map::from_str("ushers");
+        let mut found = search
+            .find_all(&m, &[RX_VEC])
+            .iter()
+            .map(|m| (search.word(m.word), m.start.x, m.end().x))
+            .collect::<Vec<_>>();
+        found.sort();
+        assert_eq!(vec![("he", 2, 3), ("hers", 2, 5), ("she", 1, 3)], found);
+
+        let demo = Map::from_str(
+            "MMMSXXMASM\nMSAMXMSMSA\nAMXSXMAAMM\nMSAMASMSMX\nXMASAMXAMM\n\
+             XXAMMXXAMA\nSMSMSASXSS\nSAXAMASAAA\nMAMMMXMMMM\nMXMXAXMASX",
+        );
+        let xmas = WordSearch::new(["XMAS"]);
+        assert_eq!(18, xmas.count(&demo, &ALL_VECS));
+        assert!(xmas
+            .find_all(&demo, &ALL_VECS)
+            .contains(&word_search::Match {
+                word: 0,
+                start: Point::new(4, 1),
+                dir: Point::new(-1, 0),
+                len: 4
+            }));
+    }
+    #[test]
+    fn word_search_overlaps_and_palindromes() {
+        use map::Map;
+        use point::{ALL_VECS, ORTHO_VECS};
+        use word_search::WordSearch;
+        let m = Map::from_str("AAA\nBXB\nABA");
+        let search = WordSearch::new(["AA", "ABA", "X", "ABA"]);
+        let count_of = |matches: &Vec<word_search::Match>, word: &str| {
+            matches
+                .iter()
+                .filter(|m| search.word(m.word) == word)
+                .count()
+        };
+        let all = search.find_all(&m, &ORTHO_VECS);
+        assert_eq!(4, count_of(&all, "AA"));
+        assert_eq!(6, count_of(&all, "ABA"));
+        assert_eq!(4, count_of(&all, "X"));
+        let distinct = search.find_distinct(&m, &ORTHO_VECS);
+        assert_eq!(2, count_of(&distinct, "AA"));
+        assert_eq!(3, count_of(&distinct, "ABA"));
+        assert_eq!(1, count_of(&distinct, "X"));
+        assert_eq!(8, count_of(&search.find_all(&m, &ALL_VECS), "X"));
+    }
+    #[test]
+    fn pattern_parse_and_variants() {
+        use pattern::{Pattern, Symmetry, Syntax};
+        let x_mas = Pattern::parse("M.S / .A. / M.S");
+        assert_eq!((3, 3), (x_mas.width(), x_mas.height()));
+        assert_eq!(1, x_mas.variants().len());
+        assert_eq!(
+            4,
+            x_mas
+                .clone()
+                .with_symmetry(Symmetry::Rotations)
+                .variants()
+                .len()
+        );
+        assert_eq!(
+            2,
+            x_mas
+                .clone()
+                .with_symmetry(Symmetry::Reflections)
+                .variants()
+                .len()
+        );
+        assert_eq!(4, x_mas.with_symmetry(Symmetry::All).variants().len());
+        let l_shape = Pattern::parse("#.\n##");
+        assert_eq!(4, l_shape.with_symmetry(Symmetry::All).variants().len());
+        let syntax = Syntax {
+            wildcard: '?',
+            ..Syntax::default()
+        };
+        let bar = Pattern::parse_with("ab?", syntax).with_symmetry(Symmetry::All);
+        assert_eq!(4, bar.variants().len());
+    }
+    #[test]
+    fn pattern_syntax() {
+        use map::Map;
+        use pattern::{Pattern, Syntax};
+        use point::Point;
+        let map = Map::from_str("a.bc\n/  x\n.c..");
+        // Escaped wildcards, separators and blanks are literal.
+        assert_eq!(4, map.find_pattern(&Pattern::parse("\\.")).len());
+        assert_eq!(
+            vec![Point::new(0, 1)],
+            map.find_pattern(&Pattern::parse("\\/"))
+        );
+        let escaped_blank = Pattern::parse("\\/\\ ");
+        assert_eq!((2, 1), (escaped_blank.width(), escaped_blank.height()));
+        assert_eq!(1, Pattern::parse("\\/ ").width());
+        assert_eq!(
+            vec![Point::new(1, 1)],
+            map.find_pattern(&Pattern::parse(" .\\  / c. "))
+        );
+        // Only new lines split rows, nothing is trimmed and `.` is a letter.
+        let syntax = Syntax {
+            wildcard: '?',
+            separator: None,
+            trim: false,
+        };
+        let pattern = Pattern::parse_with("/  x\n?c??", syntax);
+        assert_eq!((4, 2), (pattern.width(), pattern.height()));
+        assert_eq!(vec![Point::new(0, 1)], map.find_pattern(&pattern));
+        assert_eq!(
+            vec![Point::new(0, 0)],
+            map.find_pattern(&Pattern::parse_with("a.", syntax))
+        );
+    }
+    #[test]
+    fn pattern_find() {
+        use map::Map;
+        use pattern::{Pattern, Symmetry};
+        use point::Point;
+        let demo = Map::from_str(
+            ".M.S......\n..A..MSMS.\n.M.S.MAA..\n..A.ASMSM.\n.M.S.M....\n\
+             ..........\nS.S.S.S.S.\n.A.A.A.A..\nM.M.M.M.M.\n..........",
+        );
+        let x_mas = Pattern::parse("M.S / .A. / M.S");
+        assert_eq!(
+            vec![Point::new(1, 0), Point::new(1, 2)],
+            demo.find_pattern(&x_mas)
+        );
+        assert_eq!(
+            9,
+            demo.find_pattern(&x_mas.with_symmetry(Symmetry::Rotations))
+                .len()
+        );
+        let too_big = Pattern::parse("...........");
+        assert!(demo.find_pattern(&too_big).is_empty());
+    }
+    fn game_of_life(map: &str) -> automaton::Automaton<char, impl Fn(&char, &[&char]) -> char> {
+        use automaton::{Automaton, Neighbourhood};
+        Automaton::new(
+            map::Map::from_str(map),
+            Neighbourhood::Moore,
+            |cell, neighbours| match (*cell, neighbours.iter().filter(|n| ***n == '#').count()) {
+                ('#', 2) | (_, 3) => '#',
+                _ => '.',
+            },
+        )
+    }
+    #[test]
+    fn automaton_game_of_life() {
+        use cycle::Cycle;
+        let mut blinker = game_of_life(".....\n..#..\n..#..\n..#..\n.....");
+        assert!(blinker.step());
+        assert_eq!(".....\n.....\n.###.\n.....\n.....", blinker.map().dump());
+        assert_eq!(1, blinker.generation());
+        assert_eq!(None, blinker.run_until_stable(10));
+        assert_eq!(
+            Cycle {
+                prefix: 0,
+                length: 2
+            },
+            blinker.find_cycle()
+        );
+
+        let mut block = game_of_life("....\n.##.\n.##.\n....");
+        assert_eq!(Some(0), block.run_until_stable(10));
+        assert_eq!(
+            Cycle {
+                prefix: 0,
+                length: 1
+            },
+            block.find_cycle()
+        );
+
+        // A glider dies against the edges of a bounded map, but flies forever on a torus.
+        let glider = ".#....\n..#...\n###...\n......\n......\n......";
+        assert!(game_of_life(glider).run_until_stable(100).is_some());
+        let torus = game_of_life(glider).with_edges(automaton::Edges::Wrapping);
+        assert_eq!(
+            Cycle {
+                prefix: 0,
+                length: 24
+            },
+            torus.find_cycle()
+        );
+    }
+    #[test]
+    fn automaton_custom_neighbourhood() {
+        use automaton::{Automaton, Edges, Neighbourhood};
+        let shift = Neighbourhood::Custom(vec![point::Point::new(-1, 0)]);
+        let mut conveyor = Automaton::new(map::Map::from_str("#...."), shift, |_, n| *n[0])
+            .with_edges(Edges::Wrapping);
+        conveyor.run(3);
+        assert_eq!("...#.", conveyor.map().dump());
+        assert_eq!(5, conveyor.find_cycle().length);
+    }
+    #[test]
+    fn walker_demo_guard() {
+        use direction::Direction;
+        use walker::{Outcome, Walker};
+        let demo = map::Map::from_str(
+            "....#.....\n.........#\n..........\n..#.......\n.......#..\n..........\n.#........\n........#.\n#.........\n......#...",
+        );
+        let start = point::Point::new(4, 6);
+        let mut walker = Walker::new(&demo, start, Direction::Up, |p| demo.get_or(p, '.') == '#');
+        assert_eq!(Some((start, Direction::Up)), walker.next());
+        assert_eq!(Outcome::Exited, walker.run());
+        assert_eq!(41, walker.visited_positions().len());
+        assert_eq!(Some(Outcome::Exited), walker.outcome());
+        assert_eq!(None, walker.next());
+
+        let blocked = point::Point::new(3, 6);
+        let mut walker = Walker::new(&demo, start, Direction::Up, |p| {
+            *p == blocked || demo.get_or(p, '.') == '#'
+        });
+        assert!(matches!(walker.run(), Outcome::Looped(..)));
+    }
+    #[test]
+    fn walker_turn_and_border_rules() {
+        use direction::Direction;
+        use point::Point;
+        use walker::{BorderRule, Outcome, TurnRule, Walker};
+        let corridor = map::Map::from_str("#....#");
+        let wall = |p: &Point| corridor.get_or(p, '.') == '#';
+        let start = Point::new(1, 0);
+
+        // Bouncing between the two walls, the walk loops back to its start.
+        let mut walker =
+            Walker::new(&corridor, start, Direction::Right, wall).with_turn_rule(TurnRule::Reverse);
+        assert_eq!(Outcome::Looped(start, Direction::Right), walker.run());
+        assert_eq!(8, walker.path().len());
+        assert_eq!((Point::new(4, 0), Direction::Left), walker.path()[4]);
+
+        // Turning left at the wall on the right means going up, out of the map.
+        let mut walker = Walker::new(&corridor, Point::new(4, 0), Direction::Right, wall)
+            .with_turn_rule(TurnRule::Left);
+        assert_eq!(Outcome::Exited, walker.run());
+        assert_eq!(Direction::Up, walker.facing());
+        let mut walker = Walker::new(&corridor, Point::new(4, 0), Direction::Right, wall)
+            .with_turn_rule(TurnRule::Left)
+            .with_border_rule(BorderRule::Wall);
+        assert!(matches!(walker.run(), Outcome::Looped(..)));
+
+        let mut walker = Walker::new(&corridor, start, Direction::Left, wall)
+            .with_turn_rule(TurnRule::Custom(|_| Direction::Up));
+        assert_eq!(Outcome::Exited, walker.run());
+        assert_eq!(Direction::Up, walker.path()[1].1);
+
+        // Random turns never keep facing the obstacle, and are reproducible.
+        let random_walk = |seed| {
+            let mut walker = Walker::new(&corridor, start, Direction::Left, wall)
+                .with_turn_rule(TurnRule::Random { seed })
+                .with_border_rule(BorderRule::Wall);
+            walker.run();
+            walker.path().to_vec()
+        };
+        let path = random_walk(42);
+        assert_ne!(Direction::Left, path[1].1);
+        assert_eq!(path, random_walk(42));
+    }
+    #[test]
+    fn parallel_runner_sequential() {
+        use parallel::Runner;
+        let items = (1..=1000u64).collect_vec();
+        let sequential = Runner::sequential();
+        assert_eq!(1, sequential.threads());
+        assert!(!Runner::new(1).is_parallel());
+        assert_eq!(500500, sequential.sum(&items, |x| *x));
+        assert_eq!(142, sequential.count(&items, |x| x % 7 == 0));
+        assert_eq!(vec![1, 4, 9], sequential.map(&items[..3], |x| x * x));
+    }
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn parallel_runner_uses_threads() {
+        use parallel::Runner;
+        use std::collections::HashSet;
+        use std::sync::Mutex;
+        let items = (1..=1000u64).collect_vec();
+        let args = cli::Args::new(["--threads".to_string(), "4".to_string()]);
+        let (sequential, parallel) = (Runner::sequential(), Runner::from_args(&args));
+        assert_eq!(4, parallel.threads());
+        assert!(parallel.is_parallel());
+        // Slow enough items for the work to spread over several threads.
+        let threads = Mutex::new(HashSet::new());
+        let slow_square = |x: &u64| {
+            threads.lock().unwrap().insert(std::thread::current().id());
+            std::thread::sleep(std::time::Duration::from_micros(50));
+            x * x
+        };
+        assert_eq!(
+            sequential.map(&items, |x| x * x),
+            parallel.map(&items, slow_square)
+        );
+        assert!(threads.lock().unwrap().len() > 1);
+        assert_eq!(
+            sequential.count(&items, |x| x % 7 == 0),
+            parallel.count(&items, |x| x % 7 == 0)
+        );
+        assert_eq!(500500, parallel.sum(&items, |x| *x));
+    }
+    #[test]
+    fn graph_basics_and_dot() {
+        use graph::DiGraph;
+        let mut g = DiGraph::from_edges([('a', 'b'), ('b', 'c'), ('a', 'c')]);
+        assert!(!g.add_edge('a', 'b'));
+        g.add_node('z');
+        assert_eq!((4, 3), (g.node_count(), g.edge_count()));
+        assert_eq!(vec![&'b', &'c'], g.successors(&'a').collect_vec());
+        assert_eq!(vec![&'b', &'a'], g.predecessors(&'c').collect_vec());
+        assert_eq!(0, g.successors(&'?').count());
+        assert!(g.contains_edge(&'b', &'c') && !g.contains_edge(&'c', &'b'));
+        assert_eq!(
+            "digraph \"abc\" {\n    \"z\";\n    \"a\" -> \"b\";\n    \"a\" -> \"c\";\n    \"b\" -> \"c\";\n}\n",
+            g.to_dot("abc")
+        );
+        let quoted = DiGraph::from_edges([("say \"hi\"", "C:\\dir")]);
+        assert_eq!(
+            "digraph \"a\\\"b\" {\n    \"say \\\"hi\\\"\" -> \"C:\\\\dir\";\n}\n",
+            quoted.to_dot("a\"b")
+        );
+        let sub = g.subgraph(&['c', 'a']);
+        assert_eq!(vec![&'c', &'a'], sub.nodes().collect_vec());
+        assert_eq!(vec![(&'a', &'c')], sub.edges().collect_vec());
+    }
+    #[test]
+    fn graph_topological_sort_and_cycles() {
+        use graph::{DiGraph, TopoError};
+        let dag = DiGraph::from_edges([
+            (5, 11),
+            (7, 11),
+            (7, 8),
+            (3, 8),
+            (3, 10),
+            (11, 2),
+            (11, 9),
+            (11, 10),
+            (8, 9),
+        ]);
+        let sorted = dag.topological_sort().unwrap();
+        assert_eq!(vec![5, 7, 3, 11, 8, 2, 10, 9], sorted);
+        assert!(dag.edges().all(|(a, b)| {
+            sorted.iter().position(|n| n == a) < sorted.iter().position(|n| n == b)
+        }));
+        assert_eq!(
+            Err(TopoError::Ambiguous(5, 7)),
+            dag.unique_topological_sort()
+        );
+        let chain = DiGraph::from_edges([(3, 2), (1, 3), (1, 2)]);
+        assert_eq!(Ok(vec![1, 3, 2]), chain.unique_topological_sort());
+
+        let cyclic = DiGraph::from_edges([(1, 2), (2, 3), (3, 1), (3, 4), (4, 5), (5, 5), (6, 4)]);
+        assert_eq!(
+            vec![vec![5], vec![4], vec![1, 2, 3], vec![6]],
+            cyclic.strongly_connected_components()
+        );
+        assert_eq!(vec![vec![5], vec![1, 2, 3]], cyclic.cycles());
+        assert!(!cyclic.is_acyclic() && dag.is_acyclic());
+        assert_eq!(Err(TopoError::Cycle(vec![5])), cyclic.topological_sort());
+        assert!(cyclic.transitive_reduction().is_err());
+
+        // Deep enough to overflow a recursive implementation.
+        let long = DiGraph::from_edges((0..100_000).map(|i| (i, i + 1)).chain([(100_000, 0)]));
+        assert_eq!(1, long.strongly_connected_components().len());
+    }
+    #[test]
+    fn graph_reachability_closure_reduction() {
+        use graph::DiGraph;
+        let g = DiGraph::from_edges([(1, 2), (2, 3), (1, 3), (3, 4), (1, 4), (5, 4)]);
+        assert_eq!(vec![&2, &3, &4], g.reachable_from(&1));
+        assert!(g.reachable_from(&4).is_empty());
+        assert!(g.is_reachable(&2, &4) && !g.is_reachable(&4, &2) && !g.is_reachable(&1, &1));
+        let closure = g.transitive_closure();
+        assert_eq!(7, closure.edge_count());
+        assert!(closure.contains_edge(&2, &4));
+        let reduction = g.transitive_reduction().unwrap();
+        assert_eq!(
+            vec![(&1, &2), (&2, &3), (&3, &4), (&5, &4)],
+            reduction.edges().collect_vec()
+        );
+        assert_eq!(
+            closure.edges().sorted().collect_vec(),
+            reduction
+                .transitive_closure()
+                .edges()
+                .sorted()
+                .collect_vec()
+        );
+        let with_loop = DiGraph::from_edges([(1, 2), (2, 1)]);
+        assert!(with_loop.is_reachable(&1, &1));
+    }
 }
@@ -1,8 +1,10 @@
 pub use crate::point::Point;
+use crate::point::{DN_LX_VEC, DN_RX_VEC, DN_VEC, RX_VEC};
+pub use crate::rect::Rect;
 use itertools::Itertools;
 use std::borrow::Borrow;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Map<T = char> {
     pub map: Vec<Vec<T>>,
 }
@@ -76,6 +78,216 @@ impl<T> Map<T> {
     pub fn enumerate(&self) -> impl Iterator<Item = (Point, &T)> {
         self.get_all_positions().map(move |pos| (pos, &self[pos]))
     }
+
+    pub fn rows(&self) -> impl Iterator<Item = MapLine<'_, T>> {
+        self.lines_in(RX_VEC)
+    }
+    pub fn columns(&self) -> impl Iterator<Item = MapLine<'_, T>> {
+        self.lines_in(DN_VEC)
+    }
+    // Top-left to bottom-right.
+    pub fn diagonals(&self) -> impl Iterator<Item = MapLine<'_, T>> {
+        self.lines_in(DN_RX_VEC)
+    }
+    // Top-right to bottom-left.
+    pub fn anti_diagonals(&self) -> impl Iterator<Item = MapLine<'_, T>> {
+        self.lines_in(DN_LX_VEC)
+    }
+
+    // Every maximal line of cells going in the `dir` direction, which does
+    // not need to be a unit vector. Lines can be reversed with `rev()`.
+    pub fn lines_in(&self, dir: Point) -> impl Iterator<Item = MapLine<'_, T>> {
+        assert!(dir != Point::new(0, 0), "Lines need a non-zero direction");
+        self.get_all_positions_rc()
+            .filter(move |start| !self.is_pos_inside(&(start - dir)))
+            .map(move |start| {
+                let len = std::iter::successors(Some(start), |p| Some(p + dir))
+                    .take_while(|p| self.is_pos_inside(p))
+                    .count();
+                MapLine {
+                    map: self,
+                    start,
+                    dir,
+                    front: 0,
+                    back: len,
+                }
+            })
+    }
+
+    pub fn bounds(&self) -> Rect {
+        Rect::new(Point::new(0, 0), self.width(), self.height())
+    }
+
+    // Borrowed window over `rect`, indexed in its own coordinates.
+    pub fn view(&self, rect: Rect) -> MapView<'_, T> {
+        assert!(
+            rect.width == 0
+                || rect.height == 0
+                || (self.is_pos_inside(&rect.origin) && self.is_pos_inside(&rect.end())),
+            "View {:?} does not fit in a {}x{} map",
+            rect,
+            self.width(),
+            self.height()
+        );
+        MapView { map: self, rect }
+    }
+}
+
+impl<T> Map<T>
+where
+    T: Clone,
+{
+    pub fn transpose(&self) -> Map<T> {
+        Map {
+            map: (0..self.width())
+                .map(|x| self.map.iter().map(|row| row[x].clone()).collect())
+                .collect(),
+        }
+    }
+    pub fn flip_h(&self) -> Map<T> {
+        Map {
+            map: self
+                .map
+                .iter()
+                .map(|row| row.iter().rev().cloned().collect())
+                .collect(),
+        }
+    }
+    pub fn flip_v(&self) -> Map<T> {
+        Map {
+            map: self.map.iter().rev().cloned().collect(),
+        }
+    }
+    pub fn rotate_cw(&self) -> Map<T> {
+        self.transpose().flip_h()
+    }
+    pub fn rotate_ccw(&self) -> Map<T> {
+        self.transpose().flip_v()
+    }
+
+    // The 4 rotations (starting from the identity) followed by the 4
+    // rotations of the horizontally flipped map.
+    pub fn symmetries(&self) -> impl Iterator<Item = Map<T>> {
+        let rotations =
+            |start: Map<T>| std::iter::successors(Some(start), |m| Some(m.rotate_cw())).take(4);
+        rotations(self.clone()).chain(rotations(self.flip_h()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MapLine<'a, T> {
+    map: &'a Map<T>,
+    start: Point,
+    dir: Point,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T> MapLine<'a, T> {
+    pub fn start(&self) -> Point {
+        self.start
+    }
+    pub fn dir(&self) -> Point {
+        self.dir
+    }
+    fn item(&self, i: usize) -> (Point, &'a T) {
+        let pos = self.start + self.dir * i as isize;
+        (pos, &self.map[pos])
+    }
+}
+
+impl<'a, T> Iterator for MapLine<'a, T> {
+    type Item = (Point, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        (self.front < self.back).then(|| {
+            self.front += 1;
+            self.item(self.front - 1)
+        })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for MapLine<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        (self.front < self.back).then(|| {
+            self.back -= 1;
+            self.item(self.back)
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for MapLine<'_, T> {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MapView<'a, T> {
+    map: &'a Map<T>,
+    rect: Rect,
+}
+
+impl<'a, T> MapView<'a, T> {
+    pub fn width(&self) -> usize {
+        self.rect.width
+    }
+    pub fn height(&self) -> usize {
+        self.rect.height
+    }
+    // Where the view is in the underlying map.
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+    pub fn is_pos_inside(&self, pos: &Point) -> bool {
+        pos.x >= 0 && pos.x < self.width() as isize && pos.y >= 0 && pos.y < self.height() as isize
+    }
+    pub fn get(&self, pos: &Point) -> Option<&'a T> {
+        self.is_pos_inside(pos)
+            .then(|| &self.map[self.rect.origin + pos])
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = &'a [T]> {
+        let (x0, x1) = (
+            self.rect.origin.x as usize,
+            self.rect.origin.x as usize + self.width(),
+        );
+        self.map.map[self.rect.origin.y as usize..][..self.height()]
+            .iter()
+            .map(move |row| &row[x0..x1])
+    }
+
+    // Column by column, like `Map::enumerate`.
+    pub fn enumerate(&self) -> impl Iterator<Item = (Point, &'a T)> + '_ {
+        (0..self.width())
+            .cartesian_product(0..self.height())
+            .map(move |(x, y)| {
+                let pos = Point::from_usize(x, y);
+                (pos, &self.map[self.rect.origin + pos])
+            })
+    }
+
+    pub fn to_map(&self) -> Map<T>
+    where
+        T: Clone,
+    {
+        Map {
+            map: self.iter_rows().map(|row| row.to_vec()).collect(),
+        }
+    }
+}
+
+impl<'a, BPoint, T> std::ops::Index<BPoint> for MapView<'a, T>
+where
+    BPoint: Borrow<Point>,
+{
+    type Output = T;
+
+    fn index(&self, pos: BPoint) -> &Self::Output {
+        let pos = pos.borrow();
+        assert!(self.is_pos_inside(pos), "{} is outside of the view", pos);
+        &self.map[self.rect.origin + pos]
+    }
 }
 
 impl Map<char> {
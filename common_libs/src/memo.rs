@@ -0,0 +1,100 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct MemoStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub evictions: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    insertion_order: VecDeque<K>,
+    bound: Option<usize>,
+    stats: MemoStats,
+}
+
+impl<K, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Memo {
+            cache: HashMap::new(),
+            insertion_order: VecDeque::new(),
+            bound: None,
+            stats: MemoStats::default(),
+        }
+    }
+}
+
+impl<K, V> Memo<K, V>
+where
+    K: Clone + Eq + Hash,
+    V: Clone,
+{
+    pub fn new() -> Memo<K, V> {
+        Self::default()
+    }
+
+    // Once `bound` entries are cached, the oldest one is evicted for every
+    // new insertion.
+    pub fn with_bound(bound: usize) -> Memo<K, V> {
+        Memo {
+            bound: Some(bound),
+            ..Self::default()
+        }
+    }
+
+    // `compute` receives the memo itself, so it can recursively call
+    // `get_or_compute` for the sub-problems it depends on.
+    pub fn get_or_compute<F>(&mut self, key: K, compute: F) -> V
+    where
+        F: FnOnce(&mut Self, &K) -> V,
+    {
+        if let Some(value) = self.cache.get(&key) {
+            self.stats.hits += 1;
+            return value.clone();
+        }
+        self.stats.misses += 1;
+        let value = compute(self, &key);
+        self.insert(key, value.clone());
+        value
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.cache.get(key)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if let Some(bound) = self.bound {
+            if bound == 0 {
+                return;
+            }
+            if let Some(cached) = self.cache.get_mut(&key) {
+                *cached = value;
+                return;
+            }
+            while self.cache.len() >= bound {
+                let oldest = self.insertion_order.pop_front().unwrap();
+                self.cache.remove(&oldest);
+                self.stats.evictions += 1;
+            }
+            self.insertion_order.push_back(key.clone());
+        }
+        self.cache.insert(key, value);
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        self.stats
+    }
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.insertion_order.clear();
+    }
+}
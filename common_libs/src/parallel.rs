@@ -0,0 +1,90 @@
+use crate::cli::Args;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+// Runs independent checks over a slice, on a thread pool when the `parallel`
+// feature is enabled and more than one thread is asked for, one after the
+// other otherwise. Results never depend on the number of threads.
+pub struct Runner {
+    #[cfg(feature = "parallel")]
+    pool: Option<rayon::ThreadPool>,
+}
+
+impl Runner {
+    pub fn sequential() -> Runner {
+        Runner {
+            #[cfg(feature = "parallel")]
+            pool: None,
+        }
+    }
+
+    // 0 means one thread per core, 1 is the same as `sequential`.
+    #[cfg_attr(not(feature = "parallel"), allow(unused_variables))]
+    pub fn new(threads: usize) -> Runner {
+        #[cfg(feature = "parallel")]
+        if threads != 1 {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .expect("Unable to build the thread pool");
+            return Runner { pool: Some(pool) };
+        }
+        Runner::sequential()
+    }
+
+    // Reads `--threads N`, all the cores by default.
+    pub fn from_args(args: &Args) -> Runner {
+        Runner::new(args.value_or("--threads", 0))
+    }
+
+    pub fn threads(&self) -> usize {
+        #[cfg(feature = "parallel")]
+        if let Some(pool) = &self.pool {
+            return pool.current_num_threads();
+        }
+        1
+    }
+    pub fn is_parallel(&self) -> bool {
+        self.threads() > 1
+    }
+
+    pub fn count<T, P>(&self, items: &[T], predicate: P) -> usize
+    where
+        T: Sync,
+        P: Fn(&T) -> bool + Sync + Send,
+    {
+        #[cfg(feature = "parallel")]
+        if let Some(pool) = &self.pool {
+            return pool.install(|| items.par_iter().filter(|item| predicate(item)).count());
+        }
+        items.iter().filter(|item| predicate(item)).count()
+    }
+
+    pub fn sum<T, S, F>(&self, items: &[T], f: F) -> S
+    where
+        T: Sync,
+        S: Send + std::iter::Sum<S>,
+        F: Fn(&T) -> S + Sync + Send,
+    {
+        #[cfg(feature = "parallel")]
+        if let Some(pool) = &self.pool {
+            return pool.install(|| items.par_iter().map(&f).sum());
+        }
+        items.iter().map(f).sum()
+    }
+
+    // Results are in the same order as the items.
+    pub fn map<T, U, F>(&self, items: &[T], f: F) -> Vec<U>
+    where
+        T: Sync,
+        U: Send,
+        F: Fn(&T) -> U + Sync + Send,
+    {
+        #[cfg(feature = "parallel")]
+        if let Some(pool) = &self.pool {
+            return pool.install(|| items.par_iter().map(&f).collect());
+        }
+        items.iter().map(f).collect()
+    }
+}
@@ -0,0 +1,154 @@
+use crate::map::Map;
+use crate::point::Point;
+use itertools::Itertools;
+
+// How a template is written. A `\` makes the next character a literal one,
+// be it the wildcard, the separator, a blank or another `\`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Syntax {
+    pub wildcard: char,
+    // Separates rows, on top of new lines.
+    pub separator: Option<char>,
+    // Whether blanks around rows are ignored.
+    pub trim: bool,
+}
+
+impl Default for Syntax {
+    fn default() -> Self {
+        Syntax {
+            wildcard: '.',
+            separator: Some('/'),
+            trim: true,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Symmetry {
+    // Only the pattern as written.
+    Identity,
+    // The 4 rotations by 90 degrees.
+    Rotations,
+    // The pattern and its horizontal and vertical mirror images.
+    Reflections,
+    // All the 8 rotations and reflections.
+    All,
+}
+
+// Small 2D template where `None` cells match anything.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Pattern {
+    cells: Map<Option<char>>,
+    symmetry: Symmetry,
+}
+
+impl Pattern {
+    // Rows are separated either by new lines or by `/`, blanks around them
+    // are ignored, so `"M.S / .A. / M.S"` is a valid template.
+    pub fn parse(template: &str) -> Pattern {
+        Self::parse_with(template, Syntax::default())
+    }
+    pub fn parse_with(template: &str, syntax: Syntax) -> Pattern {
+        // (character, escaped)
+        let mut rows: Vec<Vec<(char, bool)>> = vec![vec![]];
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    let escaped = chars
+                        .next()
+                        .expect("Nothing to escape at the end of the pattern");
+                    rows.last_mut().unwrap().push((escaped, true));
+                }
+                '\n' => rows.push(vec![]),
+                c if Some(c) == syntax.separator => rows.push(vec![]),
+                c => rows.last_mut().unwrap().push((c, false)),
+            }
+        }
+        let is_blank = |(c, escaped): &(char, bool)| !escaped && c.is_whitespace();
+        let rows = rows
+            .into_iter()
+            .map(|mut row| {
+                if syntax.trim {
+                    let start = row.iter().take_while(|c| is_blank(c)).count();
+                    let end = row.len()
+                        - row[start..]
+                            .iter()
+                            .rev()
+                            .take_while(|c| is_blank(c))
+                            .count();
+                    row = row[start..end].to_vec();
+                }
+                row
+            })
+            .filter(|row| !row.is_empty())
+            .map(|row| {
+                row.into_iter()
+                    .map(|(c, escaped)| (escaped || c != syntax.wildcard).then_some(c))
+                    .collect_vec()
+            })
+            .collect_vec();
+        assert!(!rows.is_empty(), "Empty pattern");
+        assert!(
+            rows.iter().map(Vec::len).all_equal(),
+            "All the pattern rows must have the same length"
+        );
+        Pattern {
+            cells: Map { map: rows },
+            symmetry: Symmetry::Identity,
+        }
+    }
+
+    pub fn with_symmetry(mut self, symmetry: Symmetry) -> Pattern {
+        self.symmetry = symmetry;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.width()
+    }
+    pub fn height(&self) -> usize {
+        self.cells.height()
+    }
+
+    // Every distinct transformed version of the pattern allowed by its symmetry.
+    pub fn variants(&self) -> Vec<Map<Option<char>>> {
+        let cells = &self.cells;
+        match self.symmetry {
+            Symmetry::Identity => vec![cells.clone()],
+            Symmetry::Rotations => cells.symmetries().take(4).unique().collect(),
+            Symmetry::Reflections => [cells.clone(), cells.flip_h(), cells.flip_v()]
+                .into_iter()
+                .unique()
+                .collect(),
+            Symmetry::All => cells.symmetries().unique().collect(),
+        }
+    }
+}
+
+fn matches_at(variant: &Map<Option<char>>, map: &Map<char>, anchor: &Point) -> bool {
+    variant
+        .enumerate()
+        .all(|(p, cell)| cell.is_none_or(|c| map[anchor + p] == c))
+}
+
+impl Map<char> {
+    // Top-left corner of every placement of the pattern (or of one of its
+    // variants) in the map, row by row. Two different variants matching at the
+    // same place give the same anchor twice.
+    pub fn find_pattern(&self, pattern: &Pattern) -> Vec<Point> {
+        pattern
+            .variants()
+            .iter()
+            .filter(|v| v.width() <= self.width() && v.height() <= self.height())
+            .flat_map(|v| {
+                (0..=self.height() - v.height())
+                    .cartesian_product(0..=self.width() - v.width())
+                    .map(|(y, x)| Point::from_usize(x, y))
+                    .filter(|anchor| matches_at(v, self, anchor))
+                    .collect_vec()
+            })
+            .sorted_by_key(|p| (p.y, p.x))
+            .collect()
+    }
+}
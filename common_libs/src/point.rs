@@ -11,6 +11,15 @@ pub const DN_VEC: Point = Point { x: 0, y: 1 };
 pub const LX_VEC: Point = Point { x: -1, y: 0 };
 pub const UP_VEC: Point = Point { x: 0, y: -1 };
 pub const RX_VEC: Point = Point { x: 1, y: 0 };
+pub const DN_RX_VEC: Point = Point { x: 1, y: 1 };
+pub const DN_LX_VEC: Point = Point { x: -1, y: 1 };
+pub const UP_LX_VEC: Point = Point { x: -1, y: -1 };
+pub const UP_RX_VEC: Point = Point { x: 1, y: -1 };
+pub const ORTHO_VECS: [Point; 4] = [RX_VEC, DN_VEC, LX_VEC, UP_VEC];
+pub const DIAG_VECS: [Point; 4] = [DN_RX_VEC, DN_LX_VEC, UP_LX_VEC, UP_RX_VEC];
+pub const ALL_VECS: [Point; 8] = [
+    RX_VEC, DN_RX_VEC, DN_VEC, DN_LX_VEC, LX_VEC, UP_LX_VEC, UP_VEC, UP_RX_VEC,
+];
 
 impl Point {
     pub fn new(x: isize, y: isize) -> Point {
@@ -0,0 +1,63 @@
+use crate::point::Point;
+use itertools::Itertools;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Rect {
+    pub origin: Point,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn new(origin: Point, width: usize, height: usize) -> Rect {
+        Rect {
+            origin,
+            width,
+            height,
+        }
+    }
+    // Smallest rectangle containing both corners.
+    pub fn from_corners(a: &Point, b: &Point) -> Rect {
+        let origin = Point::new(a.x.min(b.x), a.y.min(b.y));
+        Rect {
+            origin,
+            width: (a.x.max(b.x) - origin.x + 1) as usize,
+            height: (a.y.max(b.y) - origin.y + 1) as usize,
+        }
+    }
+
+    // Bottom-right corner, inclusive.
+    pub fn end(&self) -> Point {
+        Point::new(
+            self.origin.x + self.width as isize - 1,
+            self.origin.y + self.height as isize - 1,
+        )
+    }
+
+    pub fn contains(&self, pos: &Point) -> bool {
+        pos.x >= self.origin.x
+            && pos.y >= self.origin.y
+            && pos.x < self.origin.x + self.width as isize
+            && pos.y < self.origin.y + self.height as isize
+    }
+
+    pub fn expanded_to(&self, pos: &Point) -> Rect {
+        if self.contains(pos) {
+            *self
+        } else {
+            let end = self.end();
+            Rect::from_corners(
+                &Point::new(self.origin.x.min(pos.x), self.origin.y.min(pos.y)),
+                &Point::new(end.x.max(pos.x), end.y.max(pos.y)),
+            )
+        }
+    }
+
+    // Row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Point> {
+        let origin = self.origin;
+        (0..self.height as isize)
+            .cartesian_product(0..self.width as isize)
+            .map(move |(y, x)| Point::new(origin.x + x, origin.y + y))
+    }
+}
@@ -0,0 +1,171 @@
+use crate::map::Map;
+use crate::point::Point;
+use crate::rect::Rect;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+
+// Unbounded grid: every cell that was never written holds `default`.
+#[derive(Debug, Clone)]
+pub struct SparseMap<T = char> {
+    cells: HashMap<Point, T>,
+    default: T,
+    bounds: Option<Rect>,
+}
+
+impl<T> SparseMap<T> {
+    pub fn new(default: T) -> SparseMap<T> {
+        SparseMap {
+            cells: HashMap::new(),
+            default,
+            bounds: None,
+        }
+    }
+
+    pub fn default_value(&self) -> &T {
+        &self.default
+    }
+    // Smallest rectangle containing every cell that was written, None if there is none.
+    pub fn bounds(&self) -> Option<Rect> {
+        self.bounds
+    }
+    // Number of cells that were written.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, pos: &Point) -> &T {
+        self.cells.get(pos).unwrap_or(&self.default)
+    }
+    pub fn contains(&self, pos: &Point) -> bool {
+        self.cells.contains_key(pos)
+    }
+
+    pub fn insert(&mut self, pos: Point, value: T) -> Option<T> {
+        self.grow_bounds(&pos);
+        self.cells.insert(pos, value)
+    }
+    // Resets a cell to the default value.
+    pub fn remove(&mut self, pos: &Point) -> Option<T> {
+        let removed = self.cells.remove(pos);
+        if removed.is_some() && self.bounds.is_some_and(|b| is_on_border(&b, pos)) {
+            self.bounds = self
+                .cells
+                .keys()
+                .fold(None, |bounds, p| Some(expand(bounds, p)));
+        }
+        removed
+    }
+
+    fn grow_bounds(&mut self, pos: &Point) {
+        self.bounds = Some(expand(self.bounds, pos));
+    }
+
+    // Only the cells that were written, in no particular order.
+    pub fn enumerate(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells.iter().map(|(p, v)| (*p, v))
+    }
+    pub fn ortho_neighbours<'a>(&'a self, pos: &Point) -> impl Iterator<Item = (Point, &'a T)> {
+        pos.ortho_neighbours().map(move |p| (p, self.get(&p)))
+    }
+
+    pub fn find_all_positions<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Point> + 'a
+    where
+        T: PartialEq,
+    {
+        self.cells
+            .iter()
+            .filter(move |(_, v)| *v == value)
+            .map(|(p, _)| *p)
+    }
+}
+
+impl<T> SparseMap<T>
+where
+    T: Clone + PartialEq,
+{
+    // Cells holding `default` are not stored.
+    pub fn from_map(map: &Map<T>, default: T) -> SparseMap<T> {
+        let mut sparse = SparseMap::new(default);
+        for (pos, value) in map.enumerate() {
+            if *value != sparse.default {
+                sparse.insert(pos, value.clone());
+            }
+        }
+        sparse
+    }
+
+    // Dense copy of the bounding box, its (0, 0) is `bounds().origin`. None
+    // when nothing was written: `Map` needs at least one row for its width.
+    pub fn to_map(&self) -> Option<Map<T>> {
+        let bounds = self.bounds?;
+        Some(Map {
+            map: (0..bounds.height as isize)
+                .map(|y| {
+                    (0..bounds.width as isize)
+                        .map(|x| self.get(&(bounds.origin + Point::new(x, y))).clone())
+                        .collect()
+                })
+                .collect(),
+        })
+    }
+}
+
+fn expand(bounds: Option<Rect>, pos: &Point) -> Rect {
+    match bounds {
+        Some(b) => b.expanded_to(pos),
+        None => Rect::new(*pos, 1, 1),
+    }
+}
+
+fn is_on_border(bounds: &Rect, pos: &Point) -> bool {
+    let end = bounds.end();
+    pos.x == bounds.origin.x || pos.y == bounds.origin.y || pos.x == end.x || pos.y == end.y
+}
+
+impl<BPoint, T> std::ops::Index<BPoint> for SparseMap<T>
+where
+    BPoint: Borrow<Point>,
+{
+    type Output = T;
+
+    fn index(&self, pos: BPoint) -> &Self::Output {
+        self.get(pos.borrow())
+    }
+}
+
+impl<BPoint, T> std::ops::IndexMut<BPoint> for SparseMap<T>
+where
+    BPoint: Borrow<Point>,
+    T: Clone,
+{
+    fn index_mut(&mut self, pos: BPoint) -> &mut Self::Output {
+        let pos = *pos.borrow();
+        self.grow_bounds(&pos);
+        self.cells
+            .entry(pos)
+            .or_insert_with(|| self.default.clone())
+    }
+}
+
+impl<T> std::fmt::Display for SparseMap<T>
+where
+    T: std::fmt::Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(bounds) = self.bounds else {
+            return Ok(());
+        };
+        for y in 0..bounds.height as isize {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for x in 0..bounds.width as isize {
+                write!(f, "{}", self.get(&(bounds.origin + Point::new(x, y))))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,233 @@
+use crate::map::Map;
+use crate::point::Point;
+use itertools::Itertools;
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    #[default]
+    Default,
+}
+
+impl Color {
+    fn ansi_code(&self) -> u8 {
+        match self {
+            Color::Black => 30,
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+            Color::White => 37,
+            Color::Default => 39,
+        }
+    }
+}
+
+pub trait Cell {
+    fn glyph(&self) -> char;
+    fn color(&self) -> Color {
+        Color::Default
+    }
+}
+
+impl Cell for char {
+    fn glyph(&self) -> char {
+        *self
+    }
+}
+
+impl Cell for bool {
+    fn glyph(&self) -> char {
+        if *self {
+            '#'
+        } else {
+            '.'
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub cells: Map<(char, Color)>,
+    pub caption: Option<String>,
+}
+
+impl Frame {
+    pub fn from_map<T: Cell>(map: &Map<T>) -> Frame {
+        Frame {
+            cells: Map {
+                map: map
+                    .iter_rows()
+                    .map(|row| row.iter().map(|c| (c.glyph(), c.color())).collect())
+                    .collect(),
+            },
+            caption: None,
+        }
+    }
+
+    // Points outside of the frame are silently ignored.
+    pub fn overlay(mut self, pos: &Point, glyph: char, color: Color) -> Frame {
+        self.set(pos, glyph, color);
+        self
+    }
+    // Same as `overlay`, in place.
+    pub fn set(&mut self, pos: &Point, glyph: char, color: Color) {
+        if self.cells.is_pos_inside(pos) {
+            self.cells[pos] = (glyph, color);
+        }
+    }
+
+    pub fn with_caption(mut self, caption: impl Into<String>) -> Frame {
+        self.caption = Some(caption.into());
+        self
+    }
+
+    pub fn to_text(&self) -> String {
+        self.render(|(glyph, _)| glyph.to_string())
+    }
+
+    pub fn to_ansi(&self) -> String {
+        self.render(|(glyph, color)| match color {
+            Color::Default => glyph.to_string(),
+            _ => format!("\x1b[{}m{}\x1b[0m", color.ansi_code(), glyph),
+        })
+    }
+
+    fn render<F>(&self, cell_to_string: F) -> String
+    where
+        F: Fn(&(char, Color)) -> String,
+    {
+        let grid = self
+            .cells
+            .iter_rows()
+            .map(|row| row.iter().map(&cell_to_string).collect::<String>())
+            .join("\n");
+        match &self.caption {
+            Some(caption) => format!("{}\n{}", caption, grid),
+            None => grid,
+        }
+    }
+}
+
+pub type FrameObserver<'a> = Option<&'a mut dyn FnMut(&Frame)>;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PlayMode {
+    // Frames are drawn in place, `fps` times per second.
+    Animate { fps: f64 },
+    // A frame is drawn each time enter is pressed.
+    Step,
+    // Plain text frames, one after the other, without any escape sequence.
+    Headless,
+}
+
+impl PlayMode {
+    // Understands `--animate[=FPS]`, `--step` and `--dump`, the last one
+    // given wins. The frame rate must be a positive number.
+    pub fn from_args(args: impl Iterator<Item = String>) -> Result<Option<PlayMode>, String> {
+        let mut mode = None;
+        for arg in args {
+            match arg.split_once('=') {
+                Some(("--animate", fps)) => match fps.parse::<f64>() {
+                    Ok(fps) if fps.is_finite() && fps > 0.0 => {
+                        mode = Some(PlayMode::Animate { fps })
+                    }
+                    _ => return Err(format!("Invalid frame rate: {}", fps)),
+                },
+                None if arg == "--animate" => mode = Some(PlayMode::Animate { fps: 30.0 }),
+                None if arg == "--step" => mode = Some(PlayMode::Step),
+                None if arg == "--dump" => mode = Some(PlayMode::Headless),
+                _ => {}
+            }
+        }
+        Ok(mode)
+    }
+}
+
+pub struct Player<W: Write> {
+    out: W,
+    input: Box<dyn BufRead>,
+    mode: PlayMode,
+    shown: usize,
+    quit: bool,
+}
+
+impl Player<io::Stdout> {
+    pub fn terminal(mode: PlayMode) -> Player<io::Stdout> {
+        Player::new(io::stdout(), Box::new(io::stdin().lock()), mode)
+    }
+}
+
+impl<W: Write> Player<W> {
+    pub fn new(out: W, input: Box<dyn BufRead>, mode: PlayMode) -> Player<W> {
+        Player {
+            out,
+            input,
+            mode,
+            shown: 0,
+            quit: false,
+        }
+    }
+
+    pub fn frames_shown(&self) -> usize {
+        self.shown
+    }
+
+    // In step mode, `c` switches to a continuous animation and `q` stops
+    // showing any further frame.
+    pub fn show(&mut self, frame: &Frame) -> io::Result<()> {
+        if self.quit {
+            return Ok(());
+        }
+        self.shown += 1;
+        match self.mode {
+            PlayMode::Headless => writeln!(self.out, "{}\n", frame.to_text())?,
+            PlayMode::Animate { fps } => {
+                writeln!(self.out, "\x1b[H\x1b[2J{}", frame.to_ansi())?;
+                self.out.flush()?;
+                std::thread::sleep(Duration::from_secs_f64(1.0 / fps));
+            }
+            PlayMode::Step => {
+                write!(
+                    self.out,
+                    "\x1b[H\x1b[2J{}\n[enter] next, [c] continue, [q] quit ",
+                    frame.to_ansi()
+                )?;
+                self.out.flush()?;
+                let mut command = String::new();
+                self.input.read_line(&mut command)?;
+                match command.trim() {
+                    "c" => self.mode = PlayMode::Animate { fps: 30.0 },
+                    "q" => self.quit = true,
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn play<I>(&mut self, frames: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = Frame>,
+    {
+        for frame in frames {
+            self.show(&frame)?;
+        }
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
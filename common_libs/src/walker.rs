@@ -0,0 +1,162 @@
+use crate::direction::Direction;
+use crate::map::Map;
+use crate::point::Point;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy)]
+pub enum TurnRule {
+    Right,
+    Left,
+    Reverse,
+    // Any of the three other directions, picked by a xorshift generator.
+    Random { seed: u64 },
+    Custom(fn(Direction) -> Direction),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BorderRule {
+    // Stepping out of the map ends the walk.
+    Exit,
+    // The border behaves as an obstacle.
+    Wall,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Outcome {
+    Exited,
+    // First state visited twice. With a random turn rule the walk is not
+    // guaranteed to actually repeat from there.
+    Looped(Point, Direction),
+}
+
+// Walks on a map going straight until an obstacle is ahead, then turns
+// according to its `TurnRule`. Every state the walker is in, turns included,
+// is yielded once; the walk stops when leaving the map or repeating a state.
+pub struct Walker<'a, T, F> {
+    map: &'a Map<T>,
+    is_obstacle: F,
+    pos: Point,
+    facing: Direction,
+    turn_rule: TurnRule,
+    border_rule: BorderRule,
+    rng: u64,
+    started: bool,
+    seen: HashSet<(Point, Direction)>,
+    path: Vec<(Point, Direction)>,
+    outcome: Option<Outcome>,
+}
+
+impl<'a, T, F> Walker<'a, T, F>
+where
+    F: Fn(&Point) -> bool,
+{
+    pub fn new(map: &'a Map<T>, start: Point, facing: Direction, is_obstacle: F) -> Self {
+        Walker {
+            map,
+            is_obstacle,
+            pos: start,
+            facing,
+            turn_rule: TurnRule::Right,
+            border_rule: BorderRule::Exit,
+            rng: 0,
+            started: false,
+            seen: HashSet::new(),
+            path: Vec::new(),
+            outcome: None,
+        }
+    }
+    pub fn with_turn_rule(mut self, turn_rule: TurnRule) -> Self {
+        if let TurnRule::Random { seed } = turn_rule {
+            // xorshift gets stuck on 0
+            self.rng = seed.max(1);
+        }
+        self.turn_rule = turn_rule;
+        self
+    }
+    pub fn with_border_rule(mut self, border_rule: BorderRule) -> Self {
+        self.border_rule = border_rule;
+        self
+    }
+
+    pub fn position(&self) -> Point {
+        self.pos
+    }
+    pub fn facing(&self) -> Direction {
+        self.facing
+    }
+    // None while the walk is still going.
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+    // Every state yielded so far, in order.
+    pub fn path(&self) -> &[(Point, Direction)] {
+        &self.path
+    }
+    pub fn visited_positions(&self) -> HashSet<Point> {
+        self.path.iter().map(|(p, _)| *p).collect()
+    }
+
+    // Walks until the end and tells how it ended.
+    pub fn run(&mut self) -> Outcome {
+        self.by_ref().for_each(drop);
+        self.outcome.unwrap()
+    }
+
+    fn next_random(&mut self) -> u64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
+    }
+
+    fn turn(&mut self) -> Direction {
+        match self.turn_rule {
+            TurnRule::Right => self.facing.rotate_cw(),
+            TurnRule::Left => self.facing.rotate_ccw(),
+            TurnRule::Reverse => self.facing.reverse(),
+            TurnRule::Random { .. } => {
+                let turns = 1 + self.next_random() % 3;
+                (0..turns).fold(self.facing, |d, _| d.rotate_cw())
+            }
+            TurnRule::Custom(f) => f(self.facing),
+        }
+    }
+
+    fn record(&mut self) -> Option<(Point, Direction)> {
+        let state = (self.pos, self.facing);
+        if !self.seen.insert(state) {
+            self.outcome = Some(Outcome::Looped(state.0, state.1));
+            return None;
+        }
+        self.path.push(state);
+        Some(state)
+    }
+}
+
+impl<T, F> Iterator for Walker<'_, T, F>
+where
+    F: Fn(&Point) -> bool,
+{
+    type Item = (Point, Direction);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.outcome.is_some() {
+            return None;
+        }
+        if !self.started {
+            self.started = true;
+            return self.record();
+        }
+        let ahead = self.pos + self.facing;
+        let inside = self.map.is_pos_inside(&ahead);
+        if (self.is_obstacle)(&ahead) || (!inside && self.border_rule == BorderRule::Wall) {
+            self.facing = self.turn();
+        } else if !inside {
+            self.outcome = Some(Outcome::Exited);
+            return None;
+        } else {
+            self.pos = ahead;
+        }
+        self.record()
+    }
+}
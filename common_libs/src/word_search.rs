@@ -0,0 +1,155 @@
+use crate::map::Map;
+use crate::point::Point;
+use itertools::Itertools;
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Match {
+    // Index of the word in the list given to `WordSearch::new`.
+    pub word: usize,
+    pub start: Point,
+    pub dir: Point,
+    pub len: usize,
+}
+
+impl Match {
+    pub fn end(&self) -> Point {
+        self.start + self.dir * (self.len as isize - 1)
+    }
+    pub fn cells(&self) -> impl Iterator<Item = Point> {
+        let (start, dir) = (self.start, self.dir);
+        (0..self.len as isize).map(move |i| start + dir * i)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    next: HashMap<char, usize>,
+    fail: usize,
+    // Words ending here, including the ones reachable through fail links.
+    outputs: Vec<usize>,
+}
+
+// Aho-Corasick automaton: every word is found in a single pass over each line.
+#[derive(Debug, Clone)]
+pub struct WordSearch {
+    words: Vec<String>,
+    nodes: Vec<Node>,
+}
+
+impl WordSearch {
+    pub fn new<I, S>(words: I) -> WordSearch
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let words = words.into_iter().map(Into::into).collect_vec();
+        let mut nodes = vec![Node::default()];
+        for (i, word) in words.iter().enumerate() {
+            assert!(!word.is_empty(), "Cannot search for an empty word");
+            let mut current = 0;
+            for c in word.chars() {
+                current = match nodes[current].next.get(&c) {
+                    Some(&n) => n,
+                    None => {
+                        nodes.push(Node::default());
+                        let n = nodes.len() - 1;
+                        nodes[current].next.insert(c, n);
+                        n
+                    }
+                };
+            }
+            // Repeated words are only reported once, with the first index.
+            if nodes[current].outputs.is_empty() {
+                nodes[current].outputs.push(i);
+            }
+        }
+
+        let mut queue: VecDeque<usize> = nodes[0].next.values().copied().collect();
+        while let Some(current) = queue.pop_front() {
+            let children = nodes[current]
+                .next
+                .iter()
+                .map(|(c, n)| (*c, *n))
+                .collect_vec();
+            for (c, child) in children {
+                let mut fail = nodes[current].fail;
+                while fail != 0 && !nodes[fail].next.contains_key(&c) {
+                    fail = nodes[fail].fail;
+                }
+                let fail = nodes[fail].next.get(&c).copied().unwrap_or(0);
+                nodes[child].fail = fail;
+                let inherited = nodes[fail].outputs.clone();
+                nodes[child].outputs.extend(inherited);
+                queue.push_back(child);
+            }
+        }
+        WordSearch { words, nodes }
+    }
+
+    pub fn word(&self, index: usize) -> &str {
+        &self.words[index]
+    }
+
+    fn step(&self, mut state: usize, c: char) -> usize {
+        loop {
+            if let Some(&n) = self.nodes[state].next.get(&c) {
+                return n;
+            }
+            if state == 0 {
+                return 0;
+            }
+            state = self.nodes[state].fail;
+        }
+    }
+
+    // Every occurrence of every word reading along `dir` on a single line of
+    // cells, overlapping ones included.
+    fn search_line(&self, cells: &[Point], dir: Point, matches: &mut Vec<Match>, map: &Map<char>) {
+        let mut state = 0;
+        for (i, pos) in cells.iter().enumerate() {
+            state = self.step(state, map[pos]);
+            for &word in &self.nodes[state].outputs {
+                let len = self.words[word].chars().count();
+                matches.push(Match {
+                    word,
+                    start: cells[i + 1 - len],
+                    dir,
+                    len,
+                });
+            }
+        }
+    }
+
+    // `dirs` can be `ORTHO_VECS`, `ALL_VECS` or any set of non-zero steps.
+    // The same cells read in opposite directions are two different matches:
+    // see `find_distinct` to get palindromes only once.
+    pub fn find_all(&self, map: &Map<char>, dirs: &[Point]) -> Vec<Match> {
+        let mut matches = Vec::new();
+        for dir in dirs.iter().unique() {
+            for line in map.lines_in(*dir) {
+                let cells = line.map(|(p, _)| p).collect_vec();
+                self.search_line(&cells, *dir, &mut matches, map);
+            }
+        }
+        matches
+    }
+
+    // Like `find_all`, but a word covering exactly the same cells more than
+    // once (a palindrome read both ways, a single letter in every direction)
+    // is reported only the first time.
+    pub fn find_distinct(&self, map: &Map<char>, dirs: &[Point]) -> Vec<Match> {
+        self.find_all(map, dirs)
+            .into_iter()
+            .unique_by(|m| {
+                let mut cells = m.cells().map(|p| (p.y, p.x)).collect_vec();
+                cells.sort();
+                (m.word, cells)
+            })
+            .collect()
+    }
+
+    pub fn count(&self, map: &Map<char>, dirs: &[Point]) -> usize {
+        self.find_all(map, dirs).len()
+    }
+}
@@ -0,0 +1,105 @@
+use crate::map::Map;
+use crate::point::Point;
+use std::borrow::Borrow;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WrapAxes {
+    // Torus
+    Both,
+    // Cylinders, the other axis is bounded as in a plain `Map`.
+    X,
+    Y,
+}
+
+impl WrapAxes {
+    fn wraps_x(&self) -> bool {
+        *self != WrapAxes::Y
+    }
+    fn wraps_y(&self) -> bool {
+        *self != WrapAxes::X
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WrappingMap<T = char> {
+    pub map: Map<T>,
+    pub axes: WrapAxes,
+}
+
+impl<T> WrappingMap<T> {
+    pub fn new(map: Map<T>) -> WrappingMap<T> {
+        Self::with_axes(map, WrapAxes::Both)
+    }
+    pub fn with_axes(map: Map<T>, axes: WrapAxes) -> WrappingMap<T> {
+        WrappingMap { map, axes }
+    }
+    pub fn into_inner(self) -> Map<T> {
+        self.map
+    }
+
+    pub fn width(&self) -> usize {
+        self.map.width()
+    }
+    pub fn height(&self) -> usize {
+        self.map.height()
+    }
+
+    // The in-bounds point `pos` refers to, None if it falls off a non-wrapping axis.
+    pub fn normalize(&self, pos: &Point) -> Option<Point> {
+        let (w, h) = (self.width() as isize, self.height() as isize);
+        let x = if self.axes.wraps_x() {
+            pos.x.rem_euclid(w)
+        } else {
+            pos.x
+        };
+        let y = if self.axes.wraps_y() {
+            pos.y.rem_euclid(h)
+        } else {
+            pos.y
+        };
+        let p = Point::new(x, y);
+        self.map.is_pos_inside(&p).then_some(p)
+    }
+    pub fn is_pos_inside(&self, pos: &Point) -> bool {
+        self.normalize(pos).is_some()
+    }
+
+    pub fn get(&self, pos: &Point) -> Option<&T> {
+        self.normalize(pos).map(|p| &self.map[p])
+    }
+
+    // Already normalized, neighbours falling off a non-wrapping axis are skipped.
+    pub fn ortho_neighbours<'a>(&'a self, pos: &Point) -> impl Iterator<Item = Point> + 'a {
+        pos.ortho_neighbours().filter_map(|p| self.normalize(&p))
+    }
+
+    pub fn enumerate(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.map.enumerate()
+    }
+}
+
+impl<BPoint, T> std::ops::Index<BPoint> for WrappingMap<T>
+where
+    BPoint: Borrow<Point>,
+{
+    type Output = T;
+
+    fn index(&self, pos: BPoint) -> &Self::Output {
+        match self.normalize(pos.borrow()) {
+            Some(p) => &self.map[p],
+            None => panic!("{} is out of the non-wrapping bounds", pos.borrow()),
+        }
+    }
+}
+
+impl<BPoint, T> std::ops::IndexMut<BPoint> for WrappingMap<T>
+where
+    BPoint: Borrow<Point>,
+{
+    fn index_mut(&mut self, pos: BPoint) -> &mut Self::Output {
+        match self.normalize(pos.borrow()) {
+            Some(p) => &mut self.map[p],
+            None => panic!("{} is out of the non-wrapping bounds", pos.borrow()),
+        }
+    }
+}
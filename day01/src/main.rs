@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use common_libs::Counter;
 
 fn part1(lines: &Vec<&str>) {
     println!("-------");
@@ -25,7 +25,7 @@ fn part2(lines: &Vec<&str>) {
     println!("PART 2:");
     println!("-------");
     let mut left_list = Vec::new();
-    let mut right_map = HashMap::new();
+    let mut right_counter = Counter::new();
     for line in lines {
         let mut numbers = line.split_whitespace();
         let (left, right) = (
@@ -33,21 +33,14 @@ fn part2(lines: &Vec<&str>) {
             numbers.next().unwrap().parse::<i32>().unwrap(),
         );
         left_list.push(left);
-        match right_map.get(&right) {
-            Some(n) => {
-                right_map.insert(right, n + 1);
-            }
-            None => {
-                right_map.insert(right, 1);
-            }
-        }
+        right_counter.add(right, 1);
     }
 
     println!(
         "Similarity score: {}",
         left_list
             .iter()
-            .map(|x| x * right_map.get(&x).unwrap_or(&0))
+            .map(|x| x * right_counter.get(x) as i32)
             .sum::<i32>()
     );
 }
@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Monotonicity {
+    Increasing,
+    Decreasing,
+    // Either way, as long as the whole report goes the same way.
+    Either,
+}
+
+impl std::str::FromStr for Monotonicity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "increasing" => Ok(Monotonicity::Increasing),
+            "decreasing" => Ok(Monotonicity::Decreasing),
+            "either" => Ok(Monotonicity::Either),
+            _ => Err(format!("Invalid monotonicity: {}", s)),
+        }
+    }
+}
+
+// What makes a report safe: each step between two adjacent levels is between
+// `min_step` and `max_step` (inclusive), counted in the report direction.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Rules {
+    pub min_step: i32,
+    pub max_step: i32,
+    pub monotonicity: Monotonicity,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            min_step: 1,
+            max_step: 3,
+            monotonicity: Monotonicity::Either,
+        }
+    }
+}
+
+pub struct Analyzer {
+    rules: Rules,
+}
+
+// Best way found so far to end on a kept level of a given value: how many
+// levels are kept and the index of the last one.
+#[derive(Debug, Clone, Copy)]
+struct Tail {
+    kept: usize,
+    last: usize,
+}
+
+impl Tail {
+    // More levels kept first, then the earliest last level.
+    fn better_than(&self, other: &Tail) -> bool {
+        (self.kept, std::cmp::Reverse(self.last)) > (other.kept, std::cmp::Reverse(other.last))
+    }
+}
+
+impl Analyzer {
+    pub fn new(rules: Rules) -> Result<Analyzer, String> {
+        if rules.min_step > rules.max_step {
+            return Err(format!(
+                "Empty step range: {} to {}",
+                rules.min_step, rules.max_step
+            ));
+        }
+        Ok(Analyzer { rules })
+    }
+
+    // Indices of the fewest levels to remove for the report to be safe, or
+    // None if that takes more than `tolerance` of them.
+    pub fn removals(&self, levels: &[i32], tolerance: usize) -> Option<Vec<usize>> {
+        let directions: &[i32] = match self.rules.monotonicity {
+            Monotonicity::Increasing => &[1],
+            Monotonicity::Decreasing => &[-1],
+            Monotonicity::Either => &[1, -1],
+        };
+        directions
+            .iter()
+            .filter_map(|sign| self.removals_towards(levels, tolerance, *sign))
+            .min_by_key(Vec::len)
+    }
+
+    // Always succeeds, a single level is safe.
+    pub fn min_removals(&self, levels: &[i32]) -> Vec<usize> {
+        self.removals(levels, levels.len()).unwrap()
+    }
+
+    // Goes through the levels once, keeping the best `Tail` for each value
+    // a kept level can end on. A level can only follow the tails one step
+    // below it, at most `max_step - min_step + 1` of them, so this is
+    // O(n * step range * log n) whatever the tolerance. Stops as soon as the
+    // levels seen so far already need more than `tolerance` removals.
+    fn removals_towards(&self, levels: &[i32], tolerance: usize, sign: i32) -> Option<Vec<usize>> {
+        let (min_step, max_step) = (self.rules.min_step as i64, self.rules.max_step as i64);
+        let mut tails: BTreeMap<i64, Tail> = BTreeMap::new();
+        let mut previous: Vec<Option<usize>> = vec![None; levels.len()];
+        let mut best: Option<Tail> = None;
+        for (i, level) in levels.iter().enumerate() {
+            let value = sign as i64 * *level as i64;
+            let before = tails
+                .range(value - max_step..=value - min_step)
+                .map(|(_, tail)| *tail)
+                .reduce(|a, b| if b.better_than(&a) { b } else { a });
+            previous[i] = before.map(|tail| tail.last);
+            let tail = Tail {
+                kept: before.map_or(0, |tail| tail.kept) + 1,
+                last: i,
+            };
+            let slot = tails.entry(value).or_insert(tail);
+            if tail.better_than(slot) {
+                *slot = tail;
+            }
+            if best.is_none_or(|best| tail.better_than(&best)) {
+                best = Some(tail);
+            }
+            if i + 1 - best.unwrap().kept > tolerance {
+                return None;
+            }
+        }
+
+        let mut is_kept = vec![false; levels.len()];
+        let mut current = best.map(|tail| tail.last);
+        while let Some(i) = current {
+            is_kept[i] = true;
+            current = previous[i];
+        }
+        Some((0..levels.len()).filter(|i| !is_kept[*i]).collect())
+    }
+}
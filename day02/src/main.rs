@@ -1,51 +1,187 @@
 // use std::collections::HashMap;
 // use std::cmp::Ordering;
 
+use common_libs::cli::Args;
+use common_libs::parallel::Runner;
 use itertools::Itertools;
+mod analyzer;
+use analyzer::{Analyzer, Rules};
 
-fn is_safe(levels: &Vec<i32>) -> bool {
+// Brute force, to check the analyzer against.
+#[cfg(test)]
+fn is_safe(levels: &[i32]) -> bool {
     let ordering = levels[0].cmp(&levels[1]);
     levels
         .windows(2)
         .all(|w| ordering == w[0].cmp(&w[1]) && (1..4).contains(&(w[1] - w[0]).abs()))
 }
 
-fn part1(reports: &Vec<Vec<i32>>) {
-    println!("-------");
-    println!("PART 1:");
-    println!("-------");
-    println!(
-        "Number of safe reports: {}",
-        reports.iter().filter(|&levels| is_safe(levels)).count()
-    );
+#[cfg(test)]
+fn is_safe_dampened(report: &[i32]) -> bool {
+    (0..report.len()).any(|i| is_safe(&[&report[..i], &report[i + 1..]].concat()))
 }
 
-fn part2(reports: &Vec<Vec<i32>>) {
-    println!("-------");
-    println!("PART 2:");
-    println!("-------");
-    println!(
-        "Number of safe reports: {}",
-        reports
-            .iter()
-            .filter(|&report| (0..report.len())
-                .any(|i| is_safe(&[&report[..i], &report[i + 1..]].concat())))
-            .count()
-    );
+// Reports made safe by removing at most `tolerance` levels.
+fn count_safe(
+    reports: &[Vec<i32>],
+    analyzer: &Analyzer,
+    tolerance: usize,
+    runner: &Runner,
+) -> usize {
+    runner.count(reports, |r| analyzer.removals(r, tolerance).is_some())
 }
 
-fn main() {
-    let input_filename = "input";
-    let reports = std::fs::read_to_string(input_filename)
-        .expect("Unable to read file")
+fn part1(reports: &[Vec<i32>], runner: &Runner) -> usize {
+    count_safe(
+        reports,
+        &Analyzer::new(Rules::default()).unwrap(),
+        0,
+        runner,
+    )
+}
+
+fn part2(reports: &[Vec<i32>], runner: &Runner) -> usize {
+    count_safe(
+        reports,
+        &Analyzer::new(Rules::default()).unwrap(),
+        1,
+        runner,
+    )
+}
+
+// `7 6 4 2 1: safe` or `1 3 2 4 5: remove 2 at 2`
+fn explain(analyzer: &Analyzer, report: &[i32]) -> String {
+    let removed = analyzer.min_removals(report);
+    let levels = report.iter().join(" ");
+    if removed.is_empty() {
+        return format!("{}: safe", levels);
+    }
+    let removed = removed
+        .iter()
+        .map(|i| format!("{} at {}", report[*i], i))
+        .join(", ");
+    format!("{}: remove {}", levels, removed)
+}
+
+fn parse(input_str: &str) -> Vec<Vec<i32>> {
+    input_str
         .lines()
         .map(|l| {
             l.split_whitespace()
                 .map(|x| x.parse::<i32>().unwrap())
                 .collect_vec()
         })
-        .collect_vec();
+        .collect_vec()
+}
+
+fn main() {
+    let input_filename = "input";
+    let reports = parse(&std::fs::read_to_string(input_filename).expect("Unable to read file"));
+    let args = Args::from_env();
+    let runner = Runner::from_args(&args);
+
+    let defaults = Rules::default();
+    let rules = Rules {
+        min_step: args.value_or("--min-step", defaults.min_step),
+        max_step: args.value_or("--max-step", defaults.max_step),
+        monotonicity: args.value_or("--monotonic", defaults.monotonicity),
+    };
+    let analyzer = Analyzer::new(rules).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1)
+    });
+    if args.flag("--explain") {
+        for report in &reports {
+            println!("{}", explain(&analyzer, report));
+        }
+        return;
+    }
+    if let Some(tolerance) = args.value("--tolerance") {
+        let safe = count_safe(&reports, &analyzer, tolerance, &runner);
+        println!("Number of safe reports: {}", safe);
+        return;
+    }
+
+    println!("-------");
+    println!("PART 1:");
+    println!("-------");
+    println!("Number of safe reports: {}", part1(&reports, &runner));
+    println!("-------");
+    println!("PART 2:");
+    println!("-------");
+    println!("Number of safe reports: {}", part2(&reports, &runner));
+}
+
+#[test]
+fn demo() {
+    let demo = parse(&std::fs::read_to_string("demo").expect("Unable to read file"));
+    assert_eq!(2, part1(&demo, &Runner::sequential()));
+    assert_eq!(4, part2(&demo, &Runner::sequential()));
+}
+
+#[test]
+#[cfg(feature = "parallel")]
+fn parallel_matches_sequential() {
+    let reports = parse(&std::fs::read_to_string("input").expect("Unable to read file"));
+    let (sequential, parallel) = (Runner::sequential(), Runner::new(4));
+    assert_eq!(part1(&reports, &sequential), part1(&reports, &parallel));
+    assert_eq!(part2(&reports, &sequential), part2(&reports, &parallel));
+}
+
+#[test]
+fn analyzer() {
+    use analyzer::Monotonicity;
+    let reports = parse(&std::fs::read_to_string("input").expect("Unable to read file"));
+    let analyzer = Analyzer::new(Rules::default()).unwrap();
+    for report in &reports {
+        assert_eq!(
+            is_safe(report),
+            analyzer.removals(report, 0).is_some(),
+            "{:?}",
+            report
+        );
+        assert_eq!(
+            is_safe_dampened(report),
+            analyzer.removals(report, 1).is_some(),
+            "{:?}",
+            report
+        );
+    }
+
+    let demo = parse(&std::fs::read_to_string("demo").expect("Unable to read file"));
+    let removed: Vec<Vec<usize>> = demo.iter().map(|r| analyzer.min_removals(r)).collect();
+    assert_eq!(
+        vec![vec![], vec![0, 1], vec![3, 4], vec![2], vec![3], vec![]],
+        removed
+    );
+    assert_eq!("1 3 2 4 5: remove 2 at 2", explain(&analyzer, &demo[3]));
+    assert_eq!(6, count_safe(&demo, &analyzer, 2, &Runner::sequential()));
+    assert_eq!(None, analyzer.removals(&demo[1], 1));
+
+    // Only going up, by 1 to 5.
+    let rules = Rules {
+        min_step: 1,
+        max_step: 5,
+        monotonicity: Monotonicity::Increasing,
+    };
+    let analyzer = Analyzer::new(rules).unwrap();
+    assert_eq!(vec![2], analyzer.min_removals(&[1, 2, 9, 7, 8]));
+    assert_eq!(vec![1, 2, 3, 4], analyzer.min_removals(&[7, 6, 4, 2, 1]));
+    assert_eq!(Some(vec![]), analyzer.removals(&[1, 6, 7], 0));
+    assert_eq!(Ok(Monotonicity::Decreasing), "decreasing".parse());
+    assert!(Analyzer::new(Rules {
+        min_step: 3,
+        max_step: 1,
+        ..Rules::default()
+    })
+    .is_err());
 
-    part1(&reports);
-    part2(&reports);
+    // Long reports, far more removals than any tolerance used above.
+    let analyzer = Analyzer::new(Rules::default()).unwrap();
+    let noisy: Vec<i32> = (0..100_000)
+        .map(|i| if i % 2 == 0 { i } else { -1 })
+        .collect();
+    let odd: Vec<usize> = (1..100_000).step_by(2).collect();
+    assert_eq!(odd, analyzer.min_removals(&noisy));
+    assert_eq!(None, analyzer.removals(&noisy, 10));
 }
@@ -1,35 +1,234 @@
-use regex::Regex;
-
-fn part1(input: &str) {
-    let re = Regex::new(r"mul\((?<args>\d{1,3},\d{1,3})\)").unwrap();
-    let sum = re.captures_iter(input).fold(0, |acc, cap| {
-        let (a, b) = cap["args"].split_once(',').unwrap();
-        acc + a.parse::<i32>().unwrap() * b.parse::<i32>().unwrap()
-    });
-    println!("{}", sum);
-}
-
-fn part2(input: &str) {
-    let re = Regex::new(r"(?<op>mul\((?<args>\d{1,3},\d{1,3})\)|do(n't)?\(\))").unwrap();
-    let mut active = true;
-    let sum = re.captures_iter(input).fold(0, |acc, cap| {
-        let operation = cap["op"].split_once('(').unwrap().0;
-        match operation {
-            "do" => active = true,
-            "don't" => active = false,
-            "mul" if active => {
-                let (a, b) = cap["args"].split_once(',').unwrap();
-                return acc + a.parse::<i32>().unwrap() * b.parse::<i32>().unwrap();
-            }
-            _ => {}
-        }
-        acc
-    });
-    println!("{}", sum);
+use common_libs::cli::Args;
+mod scanner;
+mod vm;
+use std::fs::File;
+use std::io::Read;
+use vm::{InstructionSet, Vm, DO, DONT, MUL};
+
+fn part1(input: impl Read) -> i64 {
+    let set = InstructionSet::new(vec![MUL]);
+    Vm::new(&set)
+        .run_reader(input)
+        .expect("Unable to read input")
+        .acc
+}
+
+fn part2(input: impl Read) -> i64 {
+    let set = InstructionSet::new(vec![MUL, DO, DONT]);
+    Vm::new(&set)
+        .run_reader(input)
+        .expect("Unable to read input")
+        .acc
 }
+
 fn main() {
     let input_filename = "input";
-    let input = std::fs::read_to_string(input_filename).expect("Unable to read file");
-    part1(&input);
-    part2(&input);
+    let open = || File::open(input_filename).expect("Unable to read file");
+    if Args::from_env().flag("--trace") {
+        let set = InstructionSet::new(vec![MUL, DO, DONT]);
+        let mut out = std::io::stdout().lock();
+        Vm::new(&set)
+            .trace(open(), &mut out)
+            .expect("Unable to write trace");
+        return;
+    }
+    println!("{}", part1(open())); // 189527826
+    println!("{}", part2(open())); // 63013756
+}
+
+#[test]
+fn demo() {
+    assert_eq!(161, part1(File::open("demo").expect("Unable to read file")));
+    assert_eq!(48, part2(File::open("demo2").expect("Unable to read file")));
+}
+
+#[test]
+fn tokens_and_trace() {
+    let set = InstructionSet::new(vec![MUL, DO, DONT]);
+    let input = "xmul(2,4)&don't()_mul(5,5)do()mul(1234,1)mul(8,5)";
+    let tokens = tokenize(&set, input);
+    let found: Vec<(usize, String)> = tokens.iter().map(|t| (t.offset, set.format(t))).collect();
+    assert_eq!(
+        vec![
+            (1, "mul(2,4)".to_string()),
+            (10, "don't()".to_string()),
+            (18, "mul(5,5)".to_string()),
+            (26, "do()".to_string()),
+            (41, "mul(8,5)".to_string()),
+        ],
+        found
+    );
+
+    let mut trace = Vec::new();
+    let state = Vm::new(&set)
+        .trace(input.as_bytes(), &mut trace)
+        .unwrap()
+        .clone();
+    assert_eq!(48, state.acc);
+    let trace = String::from_utf8(trace).unwrap();
+    let skipped: Vec<&str> = trace.lines().filter(|l| l.contains("skipped")).collect();
+    assert_eq!(1, skipped.len());
+    assert!(skipped[0].contains("mul(5,5)"));
+}
+
+#[test]
+fn new_opcode() {
+    use vm::Instruction;
+    // `add(a,b)` adds regardless of `don't()`, `neg()` flips the accumulator,
+    // `sto(r)` saves it in a register.
+    const ADD: Instruction = Instruction {
+        name: "add",
+        arity: 2,
+        max_digits: 2,
+        gated: false,
+        exec: |state, args| state.acc += args[0] + args[1],
+    };
+    const NEG: Instruction = Instruction {
+        name: "neg",
+        arity: 0,
+        max_digits: 0,
+        gated: true,
+        exec: |state, _| state.acc = -state.acc,
+    };
+    const STO: Instruction = Instruction {
+        name: "sto",
+        arity: 1,
+        max_digits: 1,
+        gated: true,
+        exec: |state, args| state.registers[args[0] as usize % 4] = state.acc,
+    };
+    let set = InstructionSet::new(vec![MUL, DO, DONT, ADD, NEG, STO]);
+    let mut vm = Vm::new(&set);
+    let input = "mul(2,3)sto(1)add(1,4)neg()don't()neg()sto(2)add(100,1)";
+    let state = vm.run_reader(input.as_bytes()).unwrap();
+    assert_eq!(-11, state.acc);
+    assert_eq!([0, 6, 0, 0], state.registers);
+    assert!(!state.enabled);
+}
+
+#[test]
+#[should_panic(expected = "Arguments of big are too long")]
+fn oversized_arguments() {
+    use vm::Instruction;
+    const BIG: Instruction = Instruction {
+        name: "big",
+        arity: 1,
+        max_digits: scanner::MAX_DIGITS + 1,
+        gated: false,
+        exec: |state, args| state.acc += args[0],
+    };
+    InstructionSet::new(vec![MUL, BIG]);
+}
+
+// Every instruction of the set appearing in `input`, in one go.
+#[cfg(test)]
+fn tokenize(set: &InstructionSet, input: &str) -> Vec<scanner::Token> {
+    let mut tokens = Vec::new();
+    scanner::scan(set.instructions(), input.as_bytes(), |token| {
+        tokens.push(token)
+    })
+    .unwrap();
+    tokens
+}
+
+// Small xorshift generator, the tests must be reproducible.
+#[cfg(test)]
+struct Rng(u64);
+
+#[cfg(test)]
+impl Rng {
+    fn next(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+
+    // Corrupted memory made of pieces of instructions, so that many of them
+    // are almost right.
+    fn corrupted_memory(&mut self, pieces: usize) -> String {
+        const PIECES: [&str; 16] = [
+            "mul(", "mul", "do()", "don't()", "do", "don't", "(", ")", ",", "1", "23", "456",
+            "7890", "m", "x", "\n",
+        ];
+        (0..pieces)
+            .map(|_| PIECES[self.next(PIECES.len())])
+            .collect()
+    }
+}
+
+// Offset and text of each match, from the regexes of the original solution.
+#[cfg(test)]
+fn regex_matches(regex: &regex::Regex, input: &str) -> Vec<(usize, String)> {
+    regex
+        .find_iter(input)
+        .map(|m| (m.start(), m.as_str().to_string()))
+        .collect()
+}
+
+// Feeds the input in chunks of random sizes, down to a single byte.
+#[cfg(test)]
+fn scanned_matches(set: &InstructionSet, input: &str, rng: &mut Rng) -> Vec<(usize, String)> {
+    let mut found = Vec::new();
+    let mut emit = |token: scanner::Token| found.push((token.offset, set.format(&token)));
+    let mut scanner = scanner::Scanner::new(set.instructions());
+    let mut rest = input.as_bytes();
+    while !rest.is_empty() {
+        let (chunk, tail) = rest.split_at(1 + rng.next(rest.len().min(12)));
+        scanner.feed(chunk, &mut emit);
+        rest = tail;
+    }
+    scanner.finish(&mut emit);
+    found
+}
+
+#[test]
+fn scanner_matches_regex() {
+    let part1_regex = regex::Regex::new(r"mul\((?<args>\d{1,3},\d{1,3})\)").unwrap();
+    let part2_regex =
+        regex::Regex::new(r"(?<op>mul\((?<args>\d{1,3},\d{1,3})\)|do(n't)?\(\))").unwrap();
+    let part1_set = InstructionSet::new(vec![MUL]);
+    let part2_set = InstructionSet::new(vec![MUL, DO, DONT]);
+    let mut rng = Rng(0x5eed);
+    for _ in 0..2000 {
+        let pieces = 1 + rng.next(40);
+        let input = rng.corrupted_memory(pieces);
+        assert_eq!(
+            regex_matches(&part1_regex, &input),
+            scanned_matches(&part1_set, &input, &mut rng),
+            "{}",
+            input
+        );
+        assert_eq!(
+            regex_matches(&part2_regex, &input),
+            scanned_matches(&part2_set, &input, &mut rng),
+            "{}",
+            input
+        );
+    }
+
+    let input = std::fs::read_to_string("input").expect("Unable to read file");
+    assert_eq!(
+        regex_matches(&part2_regex, &input),
+        scanned_matches(&part2_set, &input, &mut rng)
+    );
+}
+
+#[test]
+fn scanner_split_tokens() {
+    let set = InstructionSet::new(vec![MUL, DO, DONT]);
+    let mut found = Vec::new();
+    let mut scanner = scanner::Scanner::new(set.instructions());
+    for chunk in ["xmu", "l(2", ",4", ")don", "'", "t()mul(1,", "2"] {
+        scanner.feed(chunk.as_bytes(), &mut |t| {
+            found.push((t.offset, set.format(&t)))
+        });
+    }
+    // `mul(1,2` waits for more input, and is dropped at the end.
+    assert_eq!(2, found.len());
+    scanner.finish(&mut |t| found.push((t.offset, set.format(&t))));
+    assert_eq!(
+        vec![(1, "mul(2,4)".to_string()), (9, "don't()".to_string())],
+        found
+    );
 }
@@ -0,0 +1,191 @@
+use crate::vm::Instruction;
+use std::io::Read;
+
+pub const MAX_ARITY: usize = 4;
+// Longest instruction the scanner can recognize, in bytes.
+pub const MAX_LEN: usize = 64;
+// Most digits an argument can have and still fit in an `i64`.
+pub const MAX_DIGITS: usize = 18;
+const CHUNK_LEN: usize = 4096;
+
+// A recognized instruction in the input.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Token {
+    // Byte offset of the first character of the instruction name.
+    pub offset: usize,
+    // Index in the instruction table.
+    pub op: usize,
+    args: [i64; MAX_ARITY],
+    arity: usize,
+}
+
+impl Token {
+    pub fn args(&self) -> &[i64] {
+        &self.args[..self.arity]
+    }
+}
+
+impl Instruction {
+    // `name(` + `arity` times up to `max_digits` digits and a separator.
+    pub fn max_len(&self) -> usize {
+        self.name.len() + 1 + (self.arity * (self.max_digits + 1)).max(1)
+    }
+}
+
+enum Progress {
+    // The instruction can't start at the beginning of the window.
+    Fail,
+    // The window ends before it can be told.
+    Partial,
+    Full { len: usize, args: [i64; MAX_ARITY] },
+}
+
+fn match_instruction(instruction: &Instruction, window: &[u8]) -> Progress {
+    let mut i = 0;
+    for expected in instruction.name.bytes().chain([b'(']) {
+        match window.get(i) {
+            None => return Progress::Partial,
+            Some(c) if *c != expected => return Progress::Fail,
+            Some(_) => i += 1,
+        }
+    }
+    let mut args = [0; MAX_ARITY];
+    for (arg, value) in args.iter_mut().enumerate().take(instruction.arity) {
+        let mut digits = 0;
+        while let Some(c) = window.get(i).filter(|c| c.is_ascii_digit()) {
+            if digits == instruction.max_digits {
+                return Progress::Fail;
+            }
+            *value = *value * 10 + (c - b'0') as i64;
+            digits += 1;
+            i += 1;
+        }
+        let separator = if arg + 1 == instruction.arity {
+            b')'
+        } else {
+            b','
+        };
+        match window.get(i) {
+            None => return Progress::Partial,
+            Some(c) if digits == 0 || *c != separator => return Progress::Fail,
+            Some(_) => i += 1,
+        }
+    }
+    if instruction.arity == 0 {
+        match window.get(i) {
+            None => return Progress::Partial,
+            Some(b')') => i += 1,
+            Some(_) => return Progress::Fail,
+        }
+    }
+    Progress::Full { len: i, args }
+}
+
+// Finds instructions the way a leftmost-first regex alternation of the table
+// entries would: at each position the first entry of the table matching wins,
+// then scanning goes on after the match. Input is fed in chunks of any size,
+// only the bytes of an instruction that is still undecided are kept.
+pub struct Scanner<'a> {
+    instructions: &'a [Instruction],
+    window: [u8; MAX_LEN],
+    len: usize,
+    // Offset of `window[0]` in the whole input.
+    offset: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(instructions: &'a [Instruction]) -> Scanner<'a> {
+        for instruction in instructions {
+            assert!(
+                instruction.arity <= MAX_ARITY,
+                "Too many arguments for {}",
+                instruction.name
+            );
+            assert!(
+                instruction.max_digits <= MAX_DIGITS,
+                "Arguments of {} are too long",
+                instruction.name
+            );
+            assert!(
+                instruction.max_len() <= MAX_LEN,
+                "{} is too long",
+                instruction.name
+            );
+            assert!(!instruction.name.is_empty(), "Empty instruction name");
+        }
+        Scanner {
+            instructions,
+            window: [0; MAX_LEN],
+            len: 0,
+            offset: 0,
+        }
+    }
+
+    pub fn feed(&mut self, chunk: &[u8], emit: &mut impl FnMut(Token)) {
+        for &byte in chunk {
+            self.window[self.len] = byte;
+            self.len += 1;
+            self.resolve(false, emit);
+        }
+    }
+
+    // End of the input: instructions still waiting for more bytes are dropped.
+    pub fn finish(&mut self, emit: &mut impl FnMut(Token)) {
+        self.resolve(true, emit);
+    }
+
+    fn consume(&mut self, n: usize) {
+        self.window.copy_within(n..self.len, 0);
+        self.len -= n;
+        self.offset += n;
+    }
+
+    fn resolve(&mut self, at_end: bool, emit: &mut impl FnMut(Token)) {
+        while self.len > 0 {
+            let window = &self.window[..self.len];
+            let mut decision = None;
+            for (op, instruction) in self.instructions.iter().enumerate() {
+                match match_instruction(instruction, window) {
+                    Progress::Fail => continue,
+                    Progress::Partial if at_end => continue,
+                    Progress::Partial => return,
+                    Progress::Full { len, args } => {
+                        decision = Some((op, len, args));
+                        break;
+                    }
+                }
+            }
+            match decision {
+                Some((op, len, args)) => {
+                    emit(Token {
+                        offset: self.offset,
+                        op,
+                        args,
+                        arity: self.instructions[op].arity,
+                    });
+                    self.consume(len);
+                }
+                None => self.consume(1),
+            }
+        }
+    }
+}
+
+pub fn scan(
+    instructions: &[Instruction],
+    mut reader: impl Read,
+    mut emit: impl FnMut(Token),
+) -> std::io::Result<()> {
+    let mut scanner = Scanner::new(instructions);
+    let mut chunk = [0; CHUNK_LEN];
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => scanner.feed(&chunk[..n], &mut emit),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    scanner.finish(&mut emit);
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use crate::scanner::{scan, Scanner, Token};
+use std::io::{Read, Write};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct State {
+    // Gated instructions are skipped while false.
+    pub enabled: bool,
+    pub acc: i64,
+    // Scratch registers for user-defined instructions.
+    pub registers: [i64; 4],
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            enabled: true,
+            acc: 0,
+            registers: [0; 4],
+        }
+    }
+}
+
+// One entry of the instruction table, written `name(arg,arg,...)` in memory
+// with each argument made of 1 to `max_digits` digits.
+#[derive(Debug, Clone, Copy)]
+pub struct Instruction {
+    pub name: &'static str,
+    pub arity: usize,
+    pub max_digits: usize,
+    // Only executed when the state is enabled.
+    pub gated: bool,
+    pub exec: fn(&mut State, &[i64]),
+}
+
+pub const MUL: Instruction = Instruction {
+    name: "mul",
+    arity: 2,
+    max_digits: 3,
+    gated: true,
+    exec: |state, args| state.acc += args[0] * args[1],
+};
+pub const DO: Instruction = Instruction {
+    name: "do",
+    arity: 0,
+    max_digits: 0,
+    gated: false,
+    exec: |state, _| state.enabled = true,
+};
+pub const DONT: Instruction = Instruction {
+    name: "don't",
+    arity: 0,
+    max_digits: 0,
+    gated: false,
+    exec: |state, _| state.enabled = false,
+};
+
+pub struct InstructionSet {
+    instructions: Vec<Instruction>,
+}
+
+impl InstructionSet {
+    pub fn new(instructions: Vec<Instruction>) -> InstructionSet {
+        assert!(!instructions.is_empty(), "Empty instruction set");
+        // Checks that the scanner can handle every instruction.
+        Scanner::new(&instructions);
+        InstructionSet { instructions }
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+    pub fn instruction(&self, token: &Token) -> &Instruction {
+        &self.instructions[token.op]
+    }
+
+    // `mul(2,4)`
+    pub fn format(&self, token: &Token) -> String {
+        let args: Vec<String> = token.args().iter().map(i64::to_string).collect();
+        format!("{}({})", self.instruction(token).name, args.join(","))
+    }
+}
+
+pub struct Vm<'a> {
+    set: &'a InstructionSet,
+    pub state: State,
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(set: &'a InstructionSet) -> Vm<'a> {
+        Vm {
+            set,
+            state: State::default(),
+        }
+    }
+
+    // Returns whether the instruction was executed or skipped.
+    pub fn step(&mut self, token: &Token) -> bool {
+        let instruction = self.set.instruction(token);
+        let executed = self.state.enabled || !instruction.gated;
+        if executed {
+            (instruction.exec)(&mut self.state, token.args());
+        }
+        executed
+    }
+
+    // Instructions are executed as soon as they are read.
+    pub fn run_reader(&mut self, input: impl Read) -> std::io::Result<&State> {
+        let set = self.set;
+        scan(set.instructions(), input, |token| {
+            self.step(&token);
+        })?;
+        Ok(&self.state)
+    }
+
+    // Like `run_reader`, also writing one line per instruction met.
+    pub fn trace(&mut self, input: impl Read, out: &mut impl Write) -> std::io::Result<&State> {
+        let set = self.set;
+        let mut written = Ok(());
+        scan(set.instructions(), input, |token| {
+            let executed = self.step(&token);
+            if written.is_ok() {
+                written = writeln!(
+                    out,
+                    "{:>6} {:<16} {:<8} acc={}",
+                    token.offset,
+                    set.format(&token),
+                    if executed { "executed" } else { "skipped" },
+                    self.state.acc
+                );
+            }
+        })?;
+        written?;
+        Ok(&self.state)
+    }
+}
@@ -1,23 +1,6 @@
-use std::collections::{HashMap, HashSet};
-type Rules = HashMap<i32, HashSet<i32>>;
-fn get_rules(rules_str: &str) -> (Rules, Rules) {
-    let mut forward_rules: Rules = HashMap::new();
-    let mut backward_rules: Rules = HashMap::new();
-    for rule in rules_str.lines() {
-        let (before, after) = rule.split_once("|").unwrap();
-        let before_val = before.parse::<i32>().unwrap();
-        let after_val = after.parse::<i32>().unwrap();
-        forward_rules
-            .entry(before_val)
-            .or_insert(HashSet::new())
-            .insert(after_val);
-        backward_rules
-            .entry(after_val)
-            .or_insert(HashSet::new())
-            .insert(before_val);
-    }
-    (forward_rules, backward_rules)
-}
+use common_libs::cli::Args;
+mod rules;
+use rules::RuleGraph;
 
 fn get_pages_list(pages_str: &str) -> Vec<Vec<i32>> {
     pages_str
@@ -26,71 +9,209 @@ fn get_pages_list(pages_str: &str) -> Vec<Vec<i32>> {
         .collect()
 }
 
-fn pages_statisfy_rules(rules: &(Rules, Rules), pages: &Vec<i32>) -> bool {
-    for i in 0..pages.len() {
-        let back: Vec<i32> = pages[..i].to_vec();
-        let el: i32 = pages[i];
-        let front: Vec<i32> = pages[i + 1..].to_vec();
-        let (front_rules, back_rules) = rules;
-        if back_rules.contains_key(&el) && front.into_iter().any(|e| back_rules[&el].contains(&e)) {
-            return false;
-        }
-        if front_rules.contains_key(&el) && back.into_iter().any(|e| front_rules[&el].contains(&e))
-        {
-            return false;
-        }
-    }
-    true
-}
-
-fn reorder_pages<'a>(rules: &(Rules, Rules), pages: &Vec<i32>) -> Vec<i32> {
-    let (front_rules, back_rules) = rules;
-    let mut ordered_pages: Vec<i32> = pages.clone();
-    ordered_pages.sort_by(|a, b| {
-        if front_rules.contains_key(a) && front_rules[a].contains(b) {
-            std::cmp::Ordering::Less
-        } else if back_rules.contains_key(a) && back_rules[a].contains(b) {
-            std::cmp::Ordering::Greater
-        } else {
-            std::cmp::Ordering::Equal
-        }
-    });
-    ordered_pages
-}
-fn extract_middle(l: &Vec<i32>) -> i32 {
+fn extract_middle(l: &[i32]) -> i32 {
     let middle_index = l.len() / 2;
     l[middle_index]
 }
 
-fn part1(rules: &(Rules, Rules), pages_list: &Vec<Vec<i32>>) -> i32 {
+fn part1(rules: &RuleGraph, pages_list: &[Vec<i32>]) -> i32 {
     pages_list
-        .into_iter()
-        .filter(|pages| pages_statisfy_rules(rules, pages))
-        .map(extract_middle)
+        .iter()
+        .filter(|pages| rules.validate(pages).is_ok())
+        .map(|pages| extract_middle(pages))
         .sum()
 }
 
-fn part2(rules: &(Rules, Rules), pages_list: &Vec<Vec<i32>>) -> i32 {
+fn part2(rules: &RuleGraph, pages_list: &[Vec<i32>]) -> i32 {
     pages_list
-        .into_iter()
-        .filter(|pages| !pages_statisfy_rules(rules, pages))
-        .map(|pages| reorder_pages(rules, pages))
+        .iter()
+        .filter(|pages| rules.validate(pages).is_err())
+        .map(|pages| {
+            rules
+                .order(pages)
+                .unwrap_or_else(|e| panic!("Cannot reorder {:?}: {}", pages, e))
+        })
         .map(|pages| extract_middle(&pages))
         .sum()
 }
+
+// Why an update is rejected, and how to fix it.
+fn explain(rules: &RuleGraph, index: usize, update: &[i32]) -> String {
+    let pages: Vec<String> = update.iter().map(i32::to_string).collect();
+    let mut explanation = format!("update {}: {}\n", index + 1, pages.join(","));
+    match rules.violations(update) {
+        Ok(violations) => {
+            for v in violations {
+                explanation += &format!(
+                    "  rule {}|{} broken: {} at {}, {} at {}\n",
+                    v.before, v.after, v.before, v.before_at, v.after, v.after_at
+                );
+            }
+        }
+        Err(e) => return explanation + &format!("  cannot be fixed: {}\n", e),
+    }
+    match rules.fix_moves(update) {
+        Ok(moves) => {
+            explanation += &format!("  fixed in {} move(s):\n", moves.len());
+            for m in moves {
+                explanation += &format!("    move {} from {} to {}\n", m.page, m.from, m.to);
+            }
+        }
+        Err(e) => explanation += &format!("  cannot be fixed: {}\n", e),
+    }
+    explanation
+}
+
+fn parse(input: &str) -> (RuleGraph, Vec<Vec<i32>>) {
+    let (rules_str, pages_str) = input.split_once("\n\n").unwrap();
+    (RuleGraph::from_str(rules_str), get_pages_list(pages_str))
+}
+
 fn main() {
     let input_filename = "input";
     let input = std::fs::read_to_string(input_filename).expect("Unable to read file");
-    let input = input.split("\n\n").collect::<Vec<&str>>();
-    let (rules, pages_list) = (get_rules(input[0]), get_pages_list(input[1]));
+    let (rules, pages_list) = parse(&input);
+
+    let args = Args::from_env();
+    if args.flag("--dot") {
+        print!("{}", rules.to_dot());
+        return;
+    }
+    if args.flag("--explain") {
+        for (index, update) in pages_list.iter().enumerate() {
+            if rules.validate(update).is_err() {
+                print!("{}", explain(&rules, index, update));
+            }
+        }
+        return;
+    }
 
     println!("-------");
     println!("PART 1:");
     println!("-------");
-    println!("{}", part1(&rules, &pages_list));
+    println!("{}", part1(&rules, &pages_list)); // 6267
 
     println!("-------");
     println!("PART 2:");
     println!("-------");
-    println!("{}", part2(&rules, &pages_list));
+    println!("{}", part2(&rules, &pages_list)); // 5184
+}
+
+#[test]
+fn demo() {
+    let demo = std::fs::read_to_string("demo").expect("Unable to read file");
+    let (rules, pages_list) = parse(&demo);
+    assert_eq!(143, part1(&rules, &pages_list));
+    assert_eq!(123, part2(&rules, &pages_list));
+    assert_eq!(
+        Err(rules::OrderError::Broken(rules::Violation {
+            before: 97,
+            after: 75,
+            before_at: 1,
+            after_at: 0
+        })),
+        rules.validate(&[75, 97, 47, 61, 53])
+    );
+    assert_eq!(
+        Ok(vec![97, 75, 47, 61, 53]),
+        rules.order(&[75, 97, 47, 61, 53])
+    );
+}
+
+#[test]
+fn diagnostics() {
+    use rules::Move;
+    let demo = std::fs::read_to_string("demo").expect("Unable to read file");
+    let (rules, _) = parse(&demo);
+    let update = [97, 13, 75, 29, 47];
+    let broken: Vec<(i32, i32, usize, usize)> = rules
+        .violations(&update)
+        .unwrap()
+        .iter()
+        .map(|v| (v.before, v.after, v.before_at, v.after_at))
+        .collect();
+    assert_eq!(
+        vec![
+            (75, 13, 2, 1),
+            (29, 13, 3, 1),
+            (47, 13, 4, 1),
+            (47, 29, 4, 3)
+        ],
+        broken
+    );
+    // 97, 75 and 47 are already in order, only 13 and 29 have to move.
+    let moves = rules.fix_moves(&update).unwrap();
+    assert_eq!(
+        vec![
+            Move {
+                page: 29,
+                from: 3,
+                to: 4
+            },
+            Move {
+                page: 13,
+                from: 1,
+                to: 4
+            }
+        ],
+        moves
+    );
+    assert!(rules.fix_moves(&[75, 47, 61, 53, 29]).unwrap().is_empty());
+    assert_eq!(
+        "update 4: 75,97,47,61,53\n  rule 97|75 broken: 97 at 1, 75 at 0\n  fixed in 1 move(s):\n    move 75 from 0 to 1\n",
+        explain(&rules, 3, &[75, 97, 47, 61, 53])
+    );
+}
+
+#[test]
+fn cycles_and_ambiguities() {
+    use rules::{Move, OrderError};
+    let rules = RuleGraph::from_str("1|2\n2|3\n3|1\n3|4\n5|6");
+    // The whole rule set has a cycle, but not every update sees it.
+    assert_eq!(Ok(vec![1, 2]), rules.order(&[2, 1]));
+    assert_eq!(Ok(vec![3, 4]), rules.order(&[4, 3]));
+    // 4 is stuck behind the cycle but not part of it.
+    assert_eq!(
+        Err(OrderError::Cycle(vec![1, 2, 3])),
+        rules.order(&[4, 3, 2, 1])
+    );
+    assert!(rules.validate(&[3, 1]).is_ok());
+    assert!(rules.validate(&[3, 4, 5, 6]).is_ok());
+    assert!(rules.validate(&[1, 2, 3]).is_err());
+    assert!(rules.validate(&[2, 3, 1]).is_err());
+    assert_eq!(Err(OrderError::Ambiguous(4, 5)), rules.order(&[4, 5]));
+    assert_eq!(Err(OrderError::Ambiguous(1, 5)), rules.order(&[6, 5, 2, 1]));
+    assert_eq!(Ok(vec![7]), rules.order(&[7]));
+    // A page printed twice has no single position.
+    assert_eq!(Err(OrderError::Duplicate(2)), rules.validate(&[2, 1, 2]));
+    assert_eq!(Err(OrderError::Duplicate(1)), rules.order(&[1, 2, 1]));
+    assert_eq!(Err(OrderError::Duplicate(5)), rules.violations(&[5, 6, 5]));
+    // Moves lead to one of the valid orders, only a cycle stops them.
+    assert_eq!(
+        Ok(vec![Move {
+            page: 6,
+            from: 0,
+            to: 1
+        }]),
+        rules.fix_moves(&[6, 5, 4])
+    );
+    assert_eq!(Ok(vec![]), rules.fix_moves(&[5, 4, 6]));
+    // Moving 0 to the front is enough, even though 9 has no rule.
+    let chain = RuleGraph::from_str("0|1\n1|2");
+    assert_eq!(
+        Ok(vec![Move {
+            page: 0,
+            from: 3,
+            to: 0
+        }]),
+        chain.fix_moves(&[1, 2, 9, 0])
+    );
+    // 1 and 3 break no rule with 2, but 3 must come before 1.
+    let skip = RuleGraph::from_str("3|1");
+    assert_eq!(1, skip.fix_moves(&[1, 2, 3]).unwrap().len());
+    assert_eq!(
+        Err(OrderError::Cycle(vec![1, 2, 3])),
+        rules.fix_moves(&[3, 2, 1])
+    );
+    assert!(rules.to_dot().contains("\"3\" -> \"1\";"));
 }
@@ -0,0 +1,257 @@
+use common_libs::graph::{DiGraph, TopoError};
+use std::collections::{HashMap, HashSet};
+
+// Rule `before|after` that an update breaks, `after` is printed at
+// `after_at`, earlier than `before` at `before_at`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Violation {
+    pub before: i32,
+    pub after: i32,
+    pub before_at: usize,
+    pub after_at: usize,
+}
+
+// Takes `page` out of position `from` and puts it back so that it ends up at
+// position `to`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Move {
+    pub page: i32,
+    pub from: usize,
+    pub to: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum OrderError {
+    // Pages whose rules form a cycle.
+    Cycle(Vec<i32>),
+    // Two pages that are both free to go next, so there is more than one
+    // valid order.
+    Ambiguous(i32, i32),
+    // A page printed more than once in the update.
+    Duplicate(i32),
+    // The first rule the update breaks, see `RuleGraph::validate`.
+    Broken(Violation),
+}
+
+impl std::fmt::Display for OrderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderError::Cycle(pages) => write!(f, "Rules form a cycle among pages {:?}", pages),
+            OrderError::Ambiguous(a, b) => {
+                write!(f, "No rule tells whether {} or {} comes first", a, b)
+            }
+            OrderError::Duplicate(page) => write!(f, "Page {} is printed more than once", page),
+            OrderError::Broken(v) => write!(
+                f,
+                "Rule {}|{} broken: {} at {}, {} at {}",
+                v.before, v.after, v.before, v.before_at, v.after, v.after_at
+            ),
+        }
+    }
+}
+
+// `X|Y` rules: page X must be printed before page Y when both are in an update.
+#[derive(Debug, Clone, Default)]
+pub struct RuleGraph {
+    graph: DiGraph<i32>,
+}
+
+impl RuleGraph {
+    pub fn from_str(rules_str: &str) -> RuleGraph {
+        let mut graph = RuleGraph::default();
+        for rule in rules_str.lines() {
+            let (before, after) = rule.split_once("|").unwrap();
+            graph.add_rule(before.parse().unwrap(), after.parse().unwrap());
+        }
+        graph
+    }
+
+    pub fn add_rule(&mut self, before: i32, after: i32) {
+        self.graph.add_edge(before, after);
+    }
+
+    fn successors(&self, page: i32) -> impl Iterator<Item = i32> + '_ {
+        self.graph.successors(&page).copied()
+    }
+
+    pub fn to_dot(&self) -> String {
+        self.graph.to_dot("rules")
+    }
+
+    // Only looks at the rules of the pages in the update.
+    fn find_violations<'a>(
+        &'a self,
+        update: &'a [i32],
+        positions: &'a HashMap<i32, usize>,
+    ) -> impl Iterator<Item = Violation> + 'a {
+        update
+            .iter()
+            .enumerate()
+            .flat_map(move |(before_at, before)| {
+                self.successors(*before).filter_map(move |after| {
+                    let after_at = *positions.get(&after).filter(|j| **j < before_at)?;
+                    Some(Violation {
+                        before: *before,
+                        after,
+                        before_at,
+                        after_at,
+                    })
+                })
+            })
+    }
+
+    // Updates printing a page twice are refused before looking at any rule.
+    pub fn validate(&self, update: &[i32]) -> Result<(), OrderError> {
+        match self.find_violations(update, &positions(update)?).next() {
+            Some(violation) => Err(OrderError::Broken(violation)),
+            None => Ok(()),
+        }
+    }
+
+    // Every broken rule, by position of the page that should come first.
+    pub fn violations(&self, update: &[i32]) -> Result<Vec<Violation>, OrderError> {
+        let positions = positions(update)?;
+        let mut violations: Vec<Violation> = self.find_violations(update, &positions).collect();
+        violations.sort_by_key(|v| (v.before_at, v.after_at));
+        Ok(violations)
+    }
+
+    // Fewest moves turning the update into a valid order. The pages that
+    // stay are the most that break no rule between them, directly or through
+    // other pages of the update, see `largest_consistent`. The others are
+    // moved once each, right after the page that precedes them in an order
+    // keeping the staying ones where they are. Only a cycle makes it fail.
+    pub fn fix_moves(&self, update: &[i32]) -> Result<Vec<Move>, OrderError> {
+        positions(update)?;
+        let mut pages = self.graph.subgraph(update);
+        pages.topological_sort().map_err(order_error)?;
+        let reachable: Vec<HashSet<&i32>> = update
+            .iter()
+            .map(|p| pages.reachable_from(p).into_iter().collect())
+            .collect();
+        let inverted: Vec<Vec<bool>> = (0..update.len())
+            .map(|i| {
+                (0..update.len())
+                    .map(|j| i < j && reachable[j].contains(&update[i]))
+                    .collect()
+            })
+            .collect();
+        let staying: Vec<i32> = largest_consistent(&inverted)
+            .into_iter()
+            .map(|i| update[i])
+            .collect();
+
+        for pair in staying.windows(2) {
+            pages.add_edge(pair[0], pair[1]);
+        }
+        let ordered = pages.topological_sort().map_err(order_error)?;
+        let mut current = update.to_vec();
+        let mut moves = Vec::new();
+        for (r, page) in ordered.iter().enumerate() {
+            if staying.contains(page) {
+                continue;
+            }
+            let from = current.iter().position(|p| p == page).unwrap();
+            current.remove(from);
+            let to = match r {
+                0 => 0,
+                _ => current.iter().position(|p| *p == ordered[r - 1]).unwrap() + 1,
+            };
+            current.insert(to, *page);
+            moves.push(Move {
+                page: *page,
+                from,
+                to,
+            });
+        }
+        debug_assert_eq!(ordered, current);
+        Ok(moves)
+    }
+
+    // Topological sort of the rules between the pages of the update. The
+    // order must be the only one the rules allow.
+    pub fn order(&self, update: &[i32]) -> Result<Vec<i32>, OrderError> {
+        positions(update)?;
+        self.graph
+            .subgraph(update)
+            .unique_topological_sort()
+            .map_err(order_error)
+    }
+}
+
+fn order_error(e: TopoError<i32>) -> OrderError {
+    match e {
+        TopoError::Cycle(mut pages) => {
+            pages.sort();
+            OrderError::Cycle(pages)
+        }
+        TopoError::Ambiguous(a, b) => OrderError::Ambiguous(a.min(b), a.max(b)),
+    }
+}
+
+// Where each page is, the first page found twice is an error.
+fn positions(pages: &[i32]) -> Result<HashMap<i32, usize>, OrderError> {
+    let mut positions = HashMap::with_capacity(pages.len());
+    for (i, page) in pages.iter().enumerate() {
+        if positions.insert(*page, i).is_some() {
+            return Err(OrderError::Duplicate(*page));
+        }
+    }
+    Ok(positions)
+}
+
+// Largest set of positions whose pages can all stay, given `inverted[i][j]`
+// when the page at `j > i` must come before the one at `i`. A set can stay
+// if no two of its positions are inverted, and being inverted is transitive,
+// so this is a largest antichain of a partial order. Dilworth and König give
+// it from a maximum matching of inverted pairs: the positions reached on the
+// left but not on the right by alternating paths from unmatched ones. O(n³).
+fn largest_consistent(inverted: &[Vec<bool>]) -> Vec<usize> {
+    let n = inverted.len();
+    // `matched[j]` is the `i` whose inverted pair (i, j) is in the matching.
+    let mut matched: Vec<Option<usize>> = vec![None; n];
+    let mut free: Vec<usize> = Vec::new();
+    for i in 0..n {
+        if !augment(inverted, i, &mut matched, &mut vec![false; n]) {
+            free.push(i);
+        }
+    }
+
+    let (mut left, mut right) = (vec![false; n], vec![false; n]);
+    for i in &free {
+        left[*i] = true;
+    }
+    while let Some(i) = free.pop() {
+        for j in (0..n).filter(|j| inverted[i][*j]) {
+            if right[j] {
+                continue;
+            }
+            right[j] = true;
+            if let Some(k) = matched[j].filter(|k| !left[*k]) {
+                left[k] = true;
+                free.push(k);
+            }
+        }
+    }
+    (0..n).filter(|i| left[*i] && !right[*i]).collect()
+}
+
+// Kuhn's augmenting path from `i`, `seen` marks the `j`s already tried.
+fn augment(
+    inverted: &[Vec<bool>],
+    i: usize,
+    matched: &mut [Option<usize>],
+    seen: &mut [bool],
+) -> bool {
+    for j in 0..inverted.len() {
+        if !inverted[i][j] || seen[j] {
+            continue;
+        }
+        seen[j] = true;
+        if matched[j].is_none_or(|k| augment(inverted, k, matched, seen)) {
+            matched[j] = Some(i);
+            return true;
+        }
+    }
+    false
+}
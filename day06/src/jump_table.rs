@@ -0,0 +1,72 @@
+use common_libs::direction::Direction;
+use common_libs::map::Map;
+use common_libs::point::Point;
+
+use std::collections::HashSet;
+
+// For every cell and direction, where a guard walking straight from there
+// stops in front of an obstacle, or None if it walks out of the map.
+pub struct JumpTable {
+    stops: Map<[Option<Point>; 4]>,
+}
+
+fn distance_along(from: &Point, to: &Point, dir: &Direction) -> isize {
+    let delta = *to - *from;
+    let step = dir.to_point();
+    delta.x * step.x + delta.y * step.y
+}
+
+impl JumpTable {
+    pub fn new(map: &Map, is_obstacle: impl Fn(&Point) -> bool) -> JumpTable {
+        let mut stops = Map::from_size_value(map.width(), map.height(), [None; 4]);
+        for dir in Direction::ALL {
+            // Cells further along `dir` come first, so the next cell is always known.
+            let mut positions: Vec<Point> = map.get_all_positions().collect();
+            positions.sort_by_key(|p| -distance_along(&Point::new(0, 0), p, &dir));
+            for pos in positions {
+                let ahead = pos + dir;
+                stops[pos][dir as usize] = if is_obstacle(&ahead) {
+                    Some(pos)
+                } else if map.is_pos_inside(&ahead) {
+                    stops[ahead][dir as usize]
+                } else {
+                    None
+                };
+            }
+        }
+        JumpTable { stops }
+    }
+
+    // Like the table, but with one more obstacle in `extra`.
+    pub fn next_stop(&self, pos: &Point, dir: &Direction, extra: &Point) -> Option<Point> {
+        let stop = self.stops[pos][*dir as usize];
+        let to_extra = distance_along(pos, extra, dir);
+        let in_line = if dir.is_vertical() {
+            extra.x == pos.x
+        } else {
+            extra.y == pos.y
+        };
+        let extra_first = stop.is_none_or(|s| to_extra <= distance_along(pos, &s, dir));
+        if in_line && to_extra > 0 && extra_first {
+            Some(*extra - dir.to_point())
+        } else {
+            stop
+        }
+    }
+
+    // Jumps from obstacle to obstacle, the guard loops when it stops twice
+    // at the same place facing the same way.
+    pub fn has_loop(&self, start_pos: &Point, start_dir: &Direction, extra: &Point) -> bool {
+        let mut seen: HashSet<(Point, Direction)> = HashSet::new();
+        let mut pos = *start_pos;
+        let mut dir = *start_dir;
+        while let Some(stop) = self.next_stop(&pos, &dir, extra) {
+            pos = stop;
+            dir = dir.rotate_cw();
+            if !seen.insert((pos, dir)) {
+                return true;
+            }
+        }
+        false
+    }
+}
@@ -1,6 +1,14 @@
+mod jump_table;
+
+use common_libs::cli::Args;
+use common_libs::direction::Direction;
 use common_libs::map::Map;
+use common_libs::parallel::Runner;
 use common_libs::point::*;
+use common_libs::viz::{Color, Frame, FrameObserver, PlayMode, Player};
+use common_libs::walker::{Outcome, Walker};
 
+use jump_table::JumpTable;
 use std::collections::HashSet;
 
 const OBSTACLE: char = '#';
@@ -22,48 +30,48 @@ impl MapExtension for Map {
     }
 }
 
-fn part1(guard_start_pos: &Point, guard_start_dir: &Point, map: &Map) -> HashSet<Point> {
-    let mut guard_pos = *guard_start_pos;
-    let mut guard_dir = *guard_start_dir;
+fn part1(
+    guard_start_pos: &Point,
+    guard_start_dir: &Direction,
+    map: &Map,
+    mut observer: FrameObserver,
+) -> Vec<(Point, Direction)> {
+    let mut walker = Walker::new(map, *guard_start_pos, *guard_start_dir, |p| {
+        map.is_obstacle(p)
+    });
     let mut visited: HashSet<Point> = HashSet::new();
-    while map.is_pos_inside(&guard_pos) {
+    // Only the cell the guard left and the one it is on change each step.
+    let mut frame = Frame::from_map(map);
+    let mut previous_pos = None;
+    for (guard_pos, guard_dir) in walker.by_ref() {
         visited.insert(guard_pos);
-        let new_pos = guard_pos + guard_dir;
-        if !map.is_obstacle(&new_pos) {
-            guard_pos = new_pos;
-        } else {
-            guard_dir = guard_dir.rotate_cw();
+        if let Some(observer) = observer.as_mut() {
+            if let Some(pos) = previous_pos {
+                frame.set(&pos, 'X', Color::Cyan);
+            }
+            frame.set(&guard_pos, guard_dir.to_char(), Color::Red);
+            frame.caption = Some(format!("visited: {}", visited.len()));
+            observer(&frame);
         }
+        previous_pos = Some(guard_pos);
     }
-    visited
+    walker.path().to_vec()
 }
 
-fn has_loop(map: &Map, guard_start_pos: &Point, guard_start_dir: &Point) -> bool {
-    let mut guard_pos = *guard_start_pos;
-    let mut guard_dir = *guard_start_dir;
-    let mut visited: HashSet<(Point, Point)> = HashSet::new();
-    while map.is_pos_inside(&guard_pos) {
-        visited.insert((guard_pos, guard_dir));
-        let new_pos = guard_pos + guard_dir;
-        if !map.is_obstacle(&new_pos) {
-            guard_pos = new_pos;
-        } else {
-            guard_dir = guard_dir.rotate_cw();
-        }
-        if visited.contains(&(guard_pos, guard_dir)) {
-            return true;
-        }
-    }
-    false
+fn has_loop(map: &Map, guard_start_pos: &Point, guard_start_dir: &Direction) -> bool {
+    let mut walker = Walker::new(map, *guard_start_pos, *guard_start_dir, |p| {
+        map.is_obstacle(p)
+    });
+    matches!(walker.run(), Outcome::Looped(..))
 }
-fn part2(
+fn part2_naive(
     guard_start_pos: &Point,
-    guard_start_dir: &Point,
+    guard_start_dir: &Direction,
     map: &Map,
     visited: &HashSet<Point>,
 ) -> usize {
     visited
-        .into_iter()
+        .iter()
         .filter(|pos| {
             *pos != guard_start_pos
                 && has_loop(
@@ -75,9 +83,22 @@ fn part2(
         .count()
 }
 
-fn main() {
-    let input_filename = "input";
-    let input = std::fs::read_to_string(input_filename).expect("Unable to read file");
+// An obstacle only changes the walk from the first time the guard would
+// enter its cell: each trial starts from the state just before that.
+fn part2(map: &Map, path: &[(Point, Direction)], runner: &Runner) -> usize {
+    let table = JumpTable::new(map, |p| map.is_obstacle(p));
+    let mut tried: HashSet<Point> = HashSet::from([path[0].0]);
+    let candidates: Vec<_> = path
+        .windows(2)
+        .filter(|w| tried.insert(w[1].0))
+        .map(|w| (w[0], w[1].0))
+        .collect();
+    runner.count(&candidates, |((pos, dir), obstacle)| {
+        table.has_loop(pos, dir, obstacle)
+    })
+}
+
+fn parse(input: &str) -> (Map, Point, Direction) {
     let flat_input = input.replace('\n', "");
     let map = Map {
         map: input
@@ -86,25 +107,150 @@ fn main() {
             .collect(),
     };
     let guard_start_abs_pos = flat_input.find(|c| ARROWS.contains(&c)).unwrap();
+    // Rows are `width` long, the map does not need to be square.
     let guard_start_pos = Point {
         x: (guard_start_abs_pos % map.width()) as isize,
-        y: (guard_start_abs_pos / map.height()) as isize,
-    };
-    let guard_start_dir = match flat_input.chars().nth(guard_start_abs_pos).unwrap() {
-        'v' => DN_VEC,
-        '<' => LX_VEC,
-        '^' => UP_VEC,
-        '>' => RX_VEC,
-        _ => panic!("Invalid guard direction"),
+        y: (guard_start_abs_pos / map.width()) as isize,
     };
+    let guard_start_dir =
+        Direction::from_char(flat_input.chars().nth(guard_start_abs_pos).unwrap());
+    (map, guard_start_pos, guard_start_dir)
+}
+
+fn main() {
+    let input_filename = "input";
+    let input = std::fs::read_to_string(input_filename).expect("Unable to read file");
+    let (map, guard_start_pos, guard_start_dir) = parse(&input);
+
+    if let Some(mode) = PlayMode::from_args(std::env::args()).unwrap_or_else(|e| panic!("{}", e)) {
+        let mut player = Player::terminal(mode);
+        part1(
+            &guard_start_pos,
+            &guard_start_dir,
+            &map,
+            Some(&mut |frame| player.show(frame).expect("Unable to show frame")),
+        );
+        return;
+    }
 
     println!("-------");
     println!("PART 1:");
     println!("-------");
-    let p1 = part1(&guard_start_pos, &guard_start_dir, &map);
-    println!("{}", p1.len()); //5331
+    let path = part1(&guard_start_pos, &guard_start_dir, &map, None);
+    let visited: HashSet<Point> = path.iter().map(|(p, _)| *p).collect();
+    println!("{}", visited.len());
+    assert_eq!(5331, visited.len());
     println!("-------");
     println!("PART 2:");
     println!("-------");
-    println!("{}", part2(&guard_start_pos, &guard_start_dir, &map, &p1)); //1812
+    let args = Args::from_env();
+    let p2 = if args.flag("--naive") {
+        part2_naive(&guard_start_pos, &guard_start_dir, &map, &visited)
+    } else {
+        part2(&map, &path, &Runner::from_args(&args))
+    };
+    println!("{}", p2);
+    assert_eq!(1812, p2);
+}
+
+#[test]
+fn demo() {
+    let input = "\
+        ....#.....\n\
+        .........#\n\
+        ..........\n\
+        ..#.......\n\
+        .......#..\n\
+        ..........\n\
+        .#..^.....\n\
+        ........#.\n\
+        #.........\n\
+        ......#...";
+    let (map, start_pos, start_dir) = parse(input);
+    let path = part1(&start_pos, &start_dir, &map, None);
+    let visited: HashSet<Point> = path.iter().map(|(p, _)| *p).collect();
+    assert_eq!(41, visited.len());
+    assert_eq!(6, part2_naive(&start_pos, &start_dir, &map, &visited));
+    assert_eq!(6, part2(&map, &path, &Runner::sequential()));
+}
+
+#[test]
+fn answers() {
+    let input = std::fs::read_to_string("input").expect("Unable to read file");
+    let (map, start_pos, start_dir) = parse(&input);
+    let path = part1(&start_pos, &start_dir, &map, None);
+    let visited: HashSet<Point> = path.iter().map(|(p, _)| *p).collect();
+    assert_eq!(5331, visited.len());
+    // Same as `part2_naive`, which is too slow for a test.
+    assert_eq!(1812, part2(&map, &path, &Runner::sequential()));
+}
+
+#[test]
+#[cfg(feature = "parallel")]
+fn parallel_matches_sequential() {
+    let input = std::fs::read_to_string("input").expect("Unable to read file");
+    let (map, start_pos, start_dir) = parse(&input);
+    let path = part1(&start_pos, &start_dir, &map, None);
+    assert_eq!(
+        part2(&map, &path, &Runner::sequential()),
+        part2(&map, &path, &Runner::new(4))
+    );
+}
+
+#[test]
+fn parse_wide_map() {
+    let (map, start_pos, start_dir) = parse("......\n......\n....<.");
+    assert_eq!((6, 3), (map.width(), map.height()));
+    assert_eq!(Point::new(4, 2), start_pos);
+    assert_eq!(Direction::Left, start_dir);
+    assert_eq!('.', map[start_pos]);
+}
+
+#[test]
+fn walk_frames() {
+    let (map, start_pos, start_dir) = parse("..#..\n.....\n..^..\n.....");
+    let mut frames = Vec::new();
+    let path = part1(
+        &start_pos,
+        &start_dir,
+        &map,
+        Some(&mut |frame| frames.push(frame.to_text())),
+    );
+    assert_eq!(path.len(), frames.len());
+    assert_eq!("visited: 1\n..#..\n.....\n..^..\n.....", frames[0]);
+    assert_eq!(
+        "visited: 4\n..#..\n..XX>\n..X..\n.....",
+        frames.last().unwrap()
+    );
+}
+
+#[test]
+fn jump_table_overlay() {
+    let (map, start_pos, start_dir) = parse("..#..\n.....\n..^..\n.....");
+    let table = JumpTable::new(&map, |p| map.is_obstacle(p));
+    let far = Point::new(0, 3);
+    assert_eq!(
+        Some(Point::new(2, 1)),
+        table.next_stop(&start_pos, &start_dir, &far)
+    );
+    // An extra obstacle behind the guard, or past the real one, changes nothing.
+    let behind = Point::new(2, 3);
+    assert_eq!(
+        Some(Point::new(2, 1)),
+        table.next_stop(&start_pos, &start_dir, &behind)
+    );
+    let right = Direction::Right;
+    assert_eq!(None, table.next_stop(&start_pos, &right, &far));
+    let extra = Point::new(4, 2);
+    assert_eq!(
+        Some(Point::new(3, 2)),
+        table.next_stop(&start_pos, &right, &extra)
+    );
+    // Already facing the extra obstacle: the guard does not move.
+    let extra = Point::new(2, 1);
+    assert_eq!(
+        Some(start_pos),
+        table.next_stop(&start_pos, &start_dir, &extra)
+    );
+    assert!(!table.has_loop(&start_pos, &start_dir, &far));
 }
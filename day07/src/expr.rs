@@ -0,0 +1,140 @@
+use crate::solver::Operator;
+use std::collections::HashMap;
+
+// How a flat `a op b op c ...` sequence groups into an expression.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub enum EvalMode {
+    // Every operator has the same precedence: `((a op b) op c)`.
+    #[default]
+    LeftToRight,
+    // Every operator has the same precedence: `(a op (b op c))`.
+    RightToLeft,
+    // Each operator's own `precedence()`, `*` before `+`.
+    Standard,
+    // Precedence by operator symbol, higher binds tighter, missing ones are 0.
+    Custom(HashMap<String, u8>),
+}
+
+impl EvalMode {
+    pub fn custom<'s>(table: impl IntoIterator<Item = (&'s str, u8)>) -> EvalMode {
+        EvalMode::Custom(table.into_iter().map(|(s, p)| (s.to_string(), p)).collect())
+    }
+
+    fn precedence(&self, op: &dyn Operator) -> u8 {
+        match self {
+            EvalMode::LeftToRight | EvalMode::RightToLeft => 0,
+            EvalMode::Standard => op.precedence(),
+            EvalMode::Custom(table) => table.get(op.symbol()).copied().unwrap_or(0),
+        }
+    }
+}
+
+impl std::str::FromStr for EvalMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ltr" => Ok(EvalMode::LeftToRight),
+            "rtl" => Ok(EvalMode::RightToLeft),
+            "standard" => Ok(EvalMode::Standard),
+            // `custom:+=2,*=1`
+            _ => {
+                let table = s
+                    .strip_prefix("custom:")
+                    .ok_or(format!("Invalid evaluation mode: {}", s))?;
+                table
+                    .split(',')
+                    .map(|entry| {
+                        let (symbol, precedence) = entry.split_once('=')?;
+                        Some((symbol, precedence.parse().ok()?))
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .map(EvalMode::custom)
+                    .ok_or(format!("Invalid precedence table: {}", table))
+            }
+        }
+    }
+}
+
+pub enum Expr<'a> {
+    Num(u64),
+    BinOp(Box<Expr<'a>>, &'a dyn Operator, Box<Expr<'a>>),
+}
+
+impl<'a> Expr<'a> {
+    // Groups `operands[0] operators[0] operands[1] ...` as `mode` says, by
+    // precedence climbing.
+    pub fn build(operands: &[u64], operators: &[&'a dyn Operator], mode: &EvalMode) -> Expr<'a> {
+        assert_eq!(operands.len(), operators.len() + 1);
+        let mut next = 0;
+        Self::climb(
+            Expr::Num(operands[0]),
+            0,
+            &mut next,
+            operands,
+            operators,
+            mode,
+        )
+    }
+
+    // `next` is the index of the next operator to consume. Precedences are
+    // widened so that one more than the highest `u8` can be asked for.
+    fn climb(
+        mut lhs: Expr<'a>,
+        min_precedence: u16,
+        next: &mut usize,
+        operands: &[u64],
+        operators: &[&'a dyn Operator],
+        mode: &EvalMode,
+    ) -> Expr<'a> {
+        let right_assoc = *mode == EvalMode::RightToLeft;
+        let precedence_of = |op: &dyn Operator| u16::from(mode.precedence(op));
+        while *next < operators.len() && precedence_of(operators[*next]) >= min_precedence {
+            let op = operators[*next];
+            let precedence = precedence_of(op);
+            *next += 1;
+            let mut rhs = Expr::Num(operands[*next]);
+            while *next < operators.len() {
+                let following = precedence_of(operators[*next]);
+                if following > precedence {
+                    rhs = Self::climb(rhs, precedence + 1, next, operands, operators, mode);
+                } else if following == precedence && right_assoc {
+                    rhs = Self::climb(rhs, precedence, next, operands, operators, mode);
+                } else {
+                    break;
+                }
+            }
+            lhs = Expr::BinOp(Box::new(lhs), op, Box::new(rhs));
+        }
+        lhs
+    }
+
+    // None if an operator overflows.
+    pub fn eval(&self) -> Option<u64> {
+        match self {
+            Expr::Num(n) => Some(*n),
+            Expr::BinOp(lhs, op, rhs) => op.apply(lhs.eval()?, rhs.eval()?),
+        }
+    }
+}
+
+// Every operation but the outermost one is in parentheses: `(6 * 8 || 6) * 15`
+// would be ambiguous, `((6 * 8) || 6) * 15` is not.
+impl std::fmt::Display for Expr<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn operand(expr: &Expr, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match expr {
+                Expr::Num(n) => write!(f, "{}", n),
+                Expr::BinOp(..) => write!(f, "({})", expr),
+            }
+        }
+        match self {
+            Expr::Num(n) => write!(f, "{}", n),
+            Expr::BinOp(lhs, op, rhs) => {
+                operand(lhs, f)?;
+                write!(f, " {} ", op.symbol())?;
+                operand(rhs, f)
+            }
+        }
+    }
+}
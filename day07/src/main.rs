@@ -1,35 +1,24 @@
-fn concat(a: u64, b: u64) -> u64 {
-    let mut pow = 10;
-    while b >= pow {
-        pow *= 10;
-    }
-    a * pow + b
-}
-
-fn is_valid((result, operands): &(u64, Vec<u64>), operators: &[fn(u64, u64) -> u64]) -> bool {
-    operands[1..]
-        .iter()
-        .fold(vec![operands[0]], |acc: Vec<u64>, x| {
-            acc.iter()
-                .flat_map(|r| operators.iter().map(|f| f(*r, *x)).collect::<Vec<u64>>())
-                .collect()
-        })
-        .contains(result)
-}
+use common_libs::cli::Args;
+use common_libs::parallel::Runner;
+mod expr;
+mod solver;
+use expr::EvalMode;
+#[cfg(test)]
+use solver::Operator;
+use solver::{format_equation, Add, Concat, Mul, Solver};
 
-fn count_valids(input: &Vec<(u64, Vec<u64>)>, operators: &[fn(u64, u64) -> u64]) -> u64 {
-    input
-        .iter()
-        .filter(|x| is_valid(x, operators))
-        .map(|x| x.0)
-        .sum()
+fn count_valids(input: &[(u64, Vec<u64>)], solver: &Solver, runner: &Runner) -> u64 {
+    runner.sum(input, |(result, operands)| {
+        if solver.is_solvable(*result, operands) {
+            *result
+        } else {
+            0
+        }
+    })
 }
 
-fn main() {
-    let _input_filename = "demo";
-    let _input_filename = "input";
-    let input: Vec<(u64, Vec<u64>)> = std::fs::read_to_string(_input_filename)
-        .unwrap()
+fn parse(input_str: &str) -> Vec<(u64, Vec<u64>)> {
+    input_str
         .lines()
         .map(|line| {
             let (result, operands) = line.split_once(":").unwrap();
@@ -40,10 +29,158 @@ fn main() {
                 .collect();
             (result, operands)
         })
-        .collect();
+        .collect()
+}
+
+fn main() {
+    let _input_filename = "demo";
+    let _input_filename = "input";
+    let input = parse(&std::fs::read_to_string(_input_filename).unwrap());
+    let args = Args::from_env();
+    let runner = Runner::from_args(&args);
+    // The answers below are for the default, left to right.
+    let mode: EvalMode = args.value_or("--mode", EvalMode::LeftToRight);
+
+    let part1_solver = Solver::new(vec![Box::new(Add), Box::new(Mul)]).with_mode(mode.clone());
+    let part2_solver =
+        Solver::new(vec![Box::new(Add), Box::new(Mul), Box::new(Concat)]).with_mode(mode);
+    println!("{}", count_valids(&input, &part1_solver, &runner)); // 1399219271639
+    println!("{}", count_valids(&input, &part2_solver, &runner)); // 275791737999003
+
+    if args.flag("--witnesses") {
+        for (result, operands) in &input {
+            if let Some(expr) = part2_solver.solve_expr(*result, operands) {
+                debug_assert_eq!(Some(*result), expr.eval());
+                println!("{}", format_equation(*result, &expr));
+            }
+        }
+    }
+}
+
+#[test]
+fn demo() {
+    let input = parse(&std::fs::read_to_string("demo").unwrap());
+    let solver = Solver::new(vec![Box::new(Add), Box::new(Mul), Box::new(Concat)]);
+    assert_eq!(11387, count_valids(&input, &solver, &Runner::sequential()));
+}
+
+#[test]
+#[cfg(feature = "parallel")]
+fn parallel_matches_sequential() {
+    let input = parse(&std::fs::read_to_string("input").unwrap());
+    let solver = Solver::new(vec![Box::new(Add), Box::new(Mul), Box::new(Concat)]);
+    assert_eq!(
+        count_valids(&input, &solver, &Runner::sequential()),
+        count_valids(&input, &solver, &Runner::new(4))
+    );
+}
+
+#[test]
+fn witnesses() {
+    let solver = Solver::new(vec![Box::new(Add), Box::new(Mul), Box::new(Concat)]);
+    let input = parse(&std::fs::read_to_string("demo").unwrap());
+    for (result, operands) in &input {
+        if let Some(expr) = solver.solve_expr(*result, operands) {
+            assert_eq!(Some(*result), expr.eval());
+        }
+    }
+    let expr = solver.solve_expr(7290, &[6, 8, 6, 15]).unwrap();
+    assert_eq!("7290 = ((6 * 8) || 6) * 15", format_equation(7290, &expr));
+    assert!(solver.solve(83, &[17, 5]).is_none());
+    assert_eq!(0, solver.solve(5, &[5]).unwrap().len());
+    assert!(solver.solve(5, &[]).is_none());
+}
+
+#[test]
+fn overflows() {
+    assert_eq!(None, Mul.apply(u64::MAX / 2, 3));
+    assert_eq!(None, Concat.apply(u64::MAX / 10, 99));
+    assert_eq!(None, Concat.apply(1, u64::MAX));
+    assert_eq!(Some(12), Concat.apply(1, 2));
+    assert_eq!(Some(10), Concat.apply(1, 0));
+    assert_eq!(None, Concat.try_invert(123, 4));
+    // The solver never goes past the result, so huge operands are just rejected.
+    let solver = Solver::new(vec![Box::new(Add), Box::new(Mul), Box::new(Concat)]);
+    assert!(!solver.is_solvable(100, &[u64::MAX, u64::MAX, 100]));
+    assert!(solver.is_solvable(u64::MAX, &[u64::MAX / 5, 5, 0]));
+}
+
+#[test]
+fn zero_operands() {
+    let solver = Solver::new(vec![Box::new(Add), Box::new(Mul)]);
+    let expr = solver.solve_expr(5, &[3, 0, 5]).unwrap();
+    assert_eq!("5 = (3 * 0) + 5", format_equation(5, &expr));
+    let expr = solver.solve_expr(0, &[7, 0]).unwrap();
+    assert_eq!("0 = 7 * 0", format_equation(0, &expr));
+    assert!(solver.is_solvable(0, &[4, 6, 0]));
+    assert!(!solver.is_solvable(1, &[7, 0]));
+    // The left side still has to evaluate.
+    assert!(!solver.is_solvable(0, &[u64::MAX, u64::MAX, 0]));
+}
+
+#[test]
+fn custom_operator() {
+    struct Sub;
+    impl Operator for Sub {
+        fn symbol(&self) -> &str {
+            "-"
+        }
+        fn apply(&self, a: u64, b: u64) -> Option<u64> {
+            a.checked_sub(b)
+        }
+        fn try_invert(&self, result: u64, b: u64) -> Option<u64> {
+            result.checked_add(b)
+        }
+    }
+    let solver = Solver::new(vec![Box::new(Add), Box::new(Sub)]);
+    let expr = solver.solve_expr(3, &[10, 2, 5]).unwrap();
+    assert_eq!("3 = (10 - 2) - 5", format_equation(3, &expr));
+    let solver = solver.with_mode(EvalMode::RightToLeft);
+    let expr = solver.solve_expr(3, &[10, 2, 5]).unwrap();
+    assert_eq!("3 = 10 - (2 + 5)", format_equation(3, &expr));
+}
+
+#[test]
+fn eval_modes() {
+    let ops: [&dyn Operator; 3] = [&Add, &Mul, &Add];
+    let operands = [2, 3, 4, 5];
+    let build = |mode| expr::Expr::build(&operands, &ops, &mode);
+    let ltr = build(EvalMode::LeftToRight);
+    assert_eq!("((2 + 3) * 4) + 5", ltr.to_string());
+    assert_eq!(Some(25), ltr.eval());
+    let rtl = build(EvalMode::RightToLeft);
+    assert_eq!("2 + (3 * (4 + 5))", rtl.to_string());
+    assert_eq!(Some(29), rtl.eval());
+    let standard = build(EvalMode::Standard);
+    assert_eq!("(2 + (3 * 4)) + 5", standard.to_string());
+    assert_eq!(Some(19), standard.eval());
+    let inverted_mode = EvalMode::custom([("+", 2), ("*", 1)]);
+    let inverted = build(inverted_mode.clone());
+    assert_eq!("(2 + 3) * (4 + 5)", inverted.to_string());
+    assert_eq!(Some(45), inverted.eval());
+    assert_eq!(
+        "7",
+        expr::Expr::build(&[7], &[], &EvalMode::Standard).to_string()
+    );
+
+    let solver = Solver::new(vec![Box::new(Add), Box::new(Mul)]);
+    assert!(solver.is_solvable(3267, &[81, 40, 27]));
+    // 81 + 40 * 27 is only 1161 when `*` goes first.
+    assert!(!solver.is_solvable(1161, &[81, 40, 27]));
+    let solver = solver.with_mode(EvalMode::Standard);
+    assert!(solver.is_solvable(3267, &[81, 40, 27]));
+    assert!(solver.is_solvable(1161, &[81, 40, 27]));
+    assert_eq!(
+        "3267 = (81 * 40) + 27",
+        format_equation(3267, &solver.solve_expr(3267, &[81, 40, 27]).unwrap())
+    );
+    assert!(!solver.is_solvable(292, &[11, 6, 16, 20]));
+    assert_eq!(Ok(EvalMode::Standard), "standard".parse());
+    assert_eq!(Ok(inverted_mode), "custom:+=2,*=1".parse());
+    assert!("nope".parse::<EvalMode>().is_err());
+    assert!("custom:+2".parse::<EvalMode>().is_err());
 
-    let part1_operators = vec![|a, b| a + b, |a, b| a * b];
-    let part2_operators = vec![|a, b| a + b, |a, b| a * b, concat];
-    println!("{}", count_valids(&input, &part1_operators)); // 1399219271639
-    println!("{}", count_valids(&input, &part2_operators)); // 275791737999003
+    // The highest precedence there is.
+    let mode: EvalMode = "custom:+=255,*=254".parse().unwrap();
+    assert_eq!("(2 + 3) * (4 + 5)", build(mode).to_string());
 }
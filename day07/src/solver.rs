@@ -0,0 +1,198 @@
+use crate::expr::{EvalMode, Expr};
+
+// Binary operator that can be placed between two operands of an equation.
+pub trait Operator: Send + Sync {
+    fn symbol(&self) -> &str;
+    // `a op b`, None if it overflows.
+    fn apply(&self, a: u64, b: u64) -> Option<u64>;
+    // The `a` for which `a op b == result`, if there is exactly one.
+    fn try_invert(&self, result: u64, b: u64) -> Option<u64>;
+    // Whether `a op b == result` for any `a`.
+    fn absorbs(&self, _result: u64, _b: u64) -> bool {
+        false
+    }
+    // Used by `EvalMode::Standard`, higher binds tighter.
+    fn precedence(&self) -> u8 {
+        0
+    }
+}
+
+pub struct Add;
+pub struct Mul;
+pub struct Concat;
+
+impl Operator for Add {
+    fn symbol(&self) -> &str {
+        "+"
+    }
+    fn apply(&self, a: u64, b: u64) -> Option<u64> {
+        a.checked_add(b)
+    }
+    fn try_invert(&self, result: u64, b: u64) -> Option<u64> {
+        result.checked_sub(b)
+    }
+    fn precedence(&self) -> u8 {
+        1
+    }
+}
+
+impl Operator for Mul {
+    fn symbol(&self) -> &str {
+        "*"
+    }
+    fn apply(&self, a: u64, b: u64) -> Option<u64> {
+        a.checked_mul(b)
+    }
+    // Multiplying by 0 loses `a`, see `absorbs`.
+    fn try_invert(&self, result: u64, b: u64) -> Option<u64> {
+        (b != 0 && result.is_multiple_of(b)).then(|| result / b)
+    }
+    fn absorbs(&self, result: u64, b: u64) -> bool {
+        b == 0 && result == 0
+    }
+    fn precedence(&self) -> u8 {
+        2
+    }
+}
+
+// Smallest power of 10 greater than `b`, 10 for 0.
+fn concat_shift(b: u64) -> Option<u64> {
+    let mut pow: u64 = 10;
+    while b >= pow {
+        pow = pow.checked_mul(10)?;
+    }
+    Some(pow)
+}
+
+impl Operator for Concat {
+    fn symbol(&self) -> &str {
+        "||"
+    }
+    fn apply(&self, a: u64, b: u64) -> Option<u64> {
+        a.checked_mul(concat_shift(b)?)?.checked_add(b)
+    }
+    fn try_invert(&self, result: u64, b: u64) -> Option<u64> {
+        let pow = concat_shift(b)?;
+        (result % pow == b).then(|| result / pow)
+    }
+    // Joining digits binds tighter than arithmetic.
+    fn precedence(&self) -> u8 {
+        3
+    }
+}
+
+// Finds operators making `operands`, grouped as the evaluation mode says,
+// equal to the result. Left to right, the search starts from the result and
+// undoes the last operator first: a branch is dropped as soon as no operator
+// can be undone. An operator that gives the result whatever its left side is
+// only needs that side to evaluate. Other modes try every combination of
+// operators.
+pub struct Solver {
+    operators: Vec<Box<dyn Operator>>,
+    mode: EvalMode,
+}
+
+impl Solver {
+    pub fn new(operators: Vec<Box<dyn Operator>>) -> Solver {
+        Solver {
+            operators,
+            mode: EvalMode::LeftToRight,
+        }
+    }
+    pub fn with_mode(mut self, mode: EvalMode) -> Solver {
+        self.mode = mode;
+        self
+    }
+
+    // One operator per gap between operands, from left to right.
+    pub fn solve(&self, result: u64, operands: &[u64]) -> Option<Vec<&dyn Operator>> {
+        if operands.is_empty() {
+            return None;
+        }
+        let mut witness = Vec::with_capacity(operands.len() - 1);
+        let found = match self.mode {
+            EvalMode::LeftToRight => self.search(result, operands, &mut witness),
+            _ => self.enumerate(result, operands, &mut witness),
+        };
+        found.then_some(witness)
+    }
+
+    // The witness grouped as an expression tree.
+    pub fn solve_expr(&self, result: u64, operands: &[u64]) -> Option<Expr<'_>> {
+        let witness = self.solve(result, operands)?;
+        Some(Expr::build(operands, &witness, &self.mode))
+    }
+
+    pub fn is_solvable(&self, result: u64, operands: &[u64]) -> bool {
+        self.solve(result, operands).is_some()
+    }
+
+    fn search<'a>(
+        &'a self,
+        target: u64,
+        operands: &[u64],
+        witness: &mut Vec<&'a dyn Operator>,
+    ) -> bool {
+        let (&last, rest) = operands.split_last().unwrap();
+        if rest.is_empty() {
+            return target == last;
+        }
+        for op in &self.operators {
+            let found = match op.try_invert(target, last) {
+                Some(previous) => self.search(previous, rest, witness),
+                None => op.absorbs(target, last) && self.evaluate(rest[0], &rest[1..], witness),
+            };
+            if found {
+                witness.push(op.as_ref());
+                return true;
+            }
+        }
+        false
+    }
+
+    // Any operators for which the operands evaluate without overflowing.
+    fn evaluate<'a>(
+        &'a self,
+        value: u64,
+        operands: &[u64],
+        witness: &mut Vec<&'a dyn Operator>,
+    ) -> bool {
+        let Some((&next, rest)) = operands.split_first() else {
+            return true;
+        };
+        for op in &self.operators {
+            if let Some(value) = op.apply(value, next) {
+                witness.push(op.as_ref());
+                if self.evaluate(value, rest, witness) {
+                    return true;
+                }
+                witness.pop();
+            }
+        }
+        false
+    }
+
+    fn enumerate<'a>(
+        &'a self,
+        target: u64,
+        operands: &[u64],
+        witness: &mut Vec<&'a dyn Operator>,
+    ) -> bool {
+        if witness.len() + 1 == operands.len() {
+            return Expr::build(operands, witness, &self.mode).eval() == Some(target);
+        }
+        for op in &self.operators {
+            witness.push(op.as_ref());
+            if self.enumerate(target, operands, witness) {
+                return true;
+            }
+            witness.pop();
+        }
+        false
+    }
+}
+
+// `3267 = (81 + 40) * 27`
+pub fn format_equation(result: u64, expr: &Expr) -> String {
+    format!("{} = {}", result, expr)
+}
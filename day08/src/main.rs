@@ -1,38 +1,44 @@
-// use common_libs::map::Map;
+use common_libs::map::Map;
 use common_libs::point::Point;
-use std::collections::{HashMap, HashSet, LinkedList};
+use common_libs::rect::Rect;
+use common_libs::sparse_map::SparseMap;
+use std::collections::{HashMap, HashSet};
 
-fn part1(antennas: &HashMap<char, LinkedList<Point>>, width: usize, height: usize) -> usize {
-    let mut antinodes: HashSet<Point> = HashSet::new();
-    for pos_list in antennas.values() {
-        for pos in pos_list {
-            for p in pos_list.iter().filter(|p| **p != *pos) {
-                let dist = p - pos;
-                let antinodes_pos = pos + dist + dist;
-                if (0..height as isize).contains(&antinodes_pos.y)
-                    && (0..width as isize).contains(&antinodes_pos.x)
-                {
-                    antinodes.insert(antinodes_pos);
-                }
+// Every ordered pair of distinct antennas on the same frequency.
+fn same_frequency_pairs(antennas: &SparseMap) -> Vec<(Point, Point)> {
+    let mut by_frequency: HashMap<char, Vec<Point>> = HashMap::new();
+    for (pos, frequency) in antennas.enumerate() {
+        by_frequency.entry(*frequency).or_default().push(pos);
+    }
+    let mut pairs = Vec::new();
+    for group in by_frequency.values() {
+        for pos in group {
+            for p in group.iter().filter(|p| *p != pos) {
+                pairs.push((*pos, *p));
             }
         }
     }
+    pairs
+}
+
+fn part1(antennas: &SparseMap, area: &Rect) -> usize {
+    let mut antinodes: HashSet<Point> = HashSet::new();
+    for (pos, p) in same_frequency_pairs(antennas) {
+        let antinodes_pos = p + (p - pos);
+        if area.contains(&antinodes_pos) {
+            antinodes.insert(antinodes_pos);
+        }
+    }
     antinodes.len()
 }
-fn part2(antennas: &HashMap<char, LinkedList<Point>>, width: usize, height: usize) -> usize {
+fn part2(antennas: &SparseMap, area: &Rect) -> usize {
     let mut antinodes: HashSet<Point> = HashSet::new();
-    for pos_list in antennas.values() {
-        for pos in pos_list {
-            for p in pos_list.iter().filter(|p| **p != *pos) {
-                let dist = p - pos;
-                let mut antinodes_pos = *p;
-                while (0..height as isize).contains(&antinodes_pos.y)
-                    && (0..width as isize).contains(&antinodes_pos.x)
-                {
-                    antinodes.insert(antinodes_pos);
-                    antinodes_pos = antinodes_pos + dist;
-                }
-            }
+    for (pos, p) in same_frequency_pairs(antennas) {
+        let dist = p - pos;
+        let mut antinodes_pos = p;
+        while area.contains(&antinodes_pos) {
+            antinodes.insert(antinodes_pos);
+            antinodes_pos += dist;
         }
     }
     antinodes.len()
@@ -40,25 +46,11 @@ fn part2(antennas: &HashMap<char, LinkedList<Point>>, width: usize, height: usiz
 fn main() {
     let _input_filename = "demo";
     let _input_filename = "input";
-    let input: Vec<Vec<char>> = std::fs::read_to_string(_input_filename)
-        .expect("Unable to read file")
-        .lines()
-        .map(|l| l.chars().collect())
-        .collect();
+    let input =
+        Map::from_str(&std::fs::read_to_string(_input_filename).expect("Unable to read file"));
 
-    let antennas: HashMap<char, LinkedList<Point>> = {
-        let mut ret = HashMap::new();
-        for (y, row) in input.iter().enumerate() {
-            for (x, c) in row.iter().enumerate().filter(|(_, c)| **c != '.') {
-                let p = Point::from_usize(x, y);
-                if !ret.contains_key(c) {
-                    ret.insert(*c, LinkedList::new());
-                }
-                ret.get_mut(c).unwrap().push_back(p);
-            }
-        }
-        ret
-    };
-    assert_eq!(222, part1(&antennas, input[0].len(), input.len()));
-    assert_eq!(884, part2(&antennas, input[0].len(), input.len()));
+    let area = Rect::new(Point::new(0, 0), input.width(), input.height());
+    let antennas = SparseMap::from_map(&input, '.');
+    assert_eq!(222, part1(&antennas, &area));
+    assert_eq!(884, part2(&antennas, &area));
 }
@@ -1,5 +1,6 @@
+use common_libs::memo::Memo;
+use common_libs::Counter;
 use itertools::Itertools;
-use std::collections::HashMap;
 
 fn blink(n: u64) -> Vec<u64> {
     match n {
@@ -29,25 +30,24 @@ fn blink_n(number: u64, times: u64) -> Vec<u64> {
     }
 }
 
-fn blink_n_count_impl(number: u64, times: u64, memo: &mut HashMap<(u64, u64), usize>) -> usize {
-    if memo.contains_key(&(number, times)) {
-        memo.get(&(number, times)).unwrap().clone()
-    } else {
-        let stone_count = match times {
-            1 => blink(number).len(),
-            _ => blink(number)
-                .iter()
-                .map(|x| blink_n_count_impl(*x, times - 1, memo))
-                .sum::<usize>(),
-        };
-        memo.insert((number, times), stone_count);
-        stone_count
-    }
+type BlinkMemo = Memo<(u64, u64), usize>;
+
+fn blink_n_count(number: u64, times: u64, memo: &mut BlinkMemo) -> usize {
+    memo.get_or_compute((number, times), |memo, _| match times {
+        1 => blink(number).len(),
+        _ => blink(number)
+            .iter()
+            .map(|x| blink_n_count(*x, times - 1, memo))
+            .sum::<usize>(),
+    })
 }
 
-fn blink_n_count(number: u64, times: u64) -> usize {
-    let mut memo = HashMap::new();
-    blink_n_count_impl(number, times, &mut memo)
+fn blink_all_count(input: &[u64], times: u64) -> usize {
+    let mut stones: Counter<u64> = input.iter().copied().collect();
+    for _ in 0..times {
+        stones = stones.map_keys_flat(|x| blink(*x));
+    }
+    stones.total()
 }
 
 fn process_input(input_str: &String) -> Vec<u64> {
@@ -77,15 +77,28 @@ fn test() {
             input.iter().flat_map(|x| blink_n(*x, 6)).collect_vec()
         );
         assert_eq!(55312, input.iter().flat_map(|x| blink_n(*x, 25)).count());
+        assert_eq!(55312, blink_all_count(&input, 25));
+        let mut memo = Memo::new();
+        for times in [1, 6, 25, 75] {
+            assert_eq!(
+                blink_all_count(&input, times),
+                input
+                    .iter()
+                    .map(|x| blink_n_count(*x, times, &mut memo))
+                    .sum::<usize>()
+            );
+        }
     }
 }
 
-fn part1(input: &Vec<u64>) -> usize {
-    input.iter().map(|x| blink_n_count(*x, 25)).sum::<usize>()
+// One memo for all the stones, they keep turning into the same numbers.
+fn part1(input: &[u64]) -> usize {
+    let mut memo = Memo::new();
+    input.iter().map(|x| blink_n_count(*x, 25, &mut memo)).sum()
 }
 
-fn part2(input: &Vec<u64>) -> usize {
-    input.iter().map(|x| blink_n_count(*x, 75)).sum::<usize>()
+fn part2(input: &[u64]) -> usize {
+    blink_all_count(input, 75)
 }
 
 fn main() {
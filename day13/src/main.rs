@@ -1,4 +1,6 @@
+use common_libs::cli::Args;
 use common_libs::lineq::Bezout;
+use common_libs::parallel::Runner;
 use itertools::Itertools;
 
 struct ClawMachine {
@@ -68,28 +70,42 @@ impl ClawMachine {
     }
 }
 
-fn part1(input_str: &String) -> i64 {
-    input_str
+fn total_tokens(machines: &[ClawMachine], runner: &Runner) -> i64 {
+    runner.sum(machines, |cm| cm.get_solution().unwrap_or(0))
+}
+
+fn part1(input_str: &str, runner: &Runner) -> i64 {
+    let machines = input_str
         .split("\n\n")
         .map(ClawMachine::from_string)
-        .map(|cm| cm.get_solution().unwrap_or(0))
-        .sum()
+        .collect_vec();
+    total_tokens(&machines, runner)
 }
-fn part2(input_str: &String) -> i64 {
-    input_str
+fn part2(input_str: &str, runner: &Runner) -> i64 {
+    let machines = input_str
         .split("\n\n")
         .map(ClawMachine::from_string_p2)
-        .map(|cm| cm.get_solution().unwrap_or(0))
-        .sum()
+        .collect_vec();
+    total_tokens(&machines, runner)
 }
 
 fn main() {
+    let runner = Runner::from_args(&Args::from_env());
     let demo_filename = "demo";
     let demo_str = std::fs::read_to_string(demo_filename).expect("Unable to read file");
-    assert_eq!(480, part1(&demo_str));
+    assert_eq!(480, part1(&demo_str, &runner));
 
     let input_filename = "input";
     let input_str = std::fs::read_to_string(input_filename).expect("Unable to read file");
-    assert_eq!(27157, part1(&input_str));
-    assert_eq!(104015411578548, part2(&input_str));
+    assert_eq!(27157, part1(&input_str, &runner));
+    assert_eq!(104015411578548, part2(&input_str, &runner));
+}
+
+#[test]
+#[cfg(feature = "parallel")]
+fn parallel_matches_sequential() {
+    let input_str = std::fs::read_to_string("input").expect("Unable to read file");
+    let (sequential, parallel) = (Runner::sequential(), Runner::new(4));
+    assert_eq!(part1(&input_str, &sequential), part1(&input_str, &parallel));
+    assert_eq!(part2(&input_str, &sequential), part2(&input_str, &parallel));
 }
@@ -0,0 +1,151 @@
+use common_libs::map::{Map, Point};
+use itertools::Itertools;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EdgeMode {
+    // Robots teleport to the opposite side (torus).
+    Wrap,
+    // Robots stop moving along an axis once they reach a wall.
+    Clamp,
+    // Robots bounce back from walls.
+    Bounce,
+}
+
+impl std::str::FromStr for EdgeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wrap" => Ok(EdgeMode::Wrap),
+            "clamp" => Ok(EdgeMode::Clamp),
+            "bounce" => Ok(EdgeMode::Bounce),
+            _ => Err(format!("Invalid edge mode: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Robot {
+    pub p: Point,
+    pub v: Point,
+}
+
+impl Robot {
+    pub fn from_string(input_str: &str) -> Robot {
+        let (p, v) = input_str
+            .split_whitespace()
+            .map(|x| x.split_once('=').unwrap().1)
+            .map(Point::from_string)
+            .collect_tuple()
+            .unwrap();
+        Robot { p, v }
+    }
+}
+
+// Position and velocity along a single axis of length `size` after `n` ticks.
+fn move_on_axis(p: isize, v: isize, n: isize, size: isize, mode: EdgeMode) -> (isize, isize) {
+    let unbounded = p + n * v;
+    match mode {
+        EdgeMode::Wrap => (unbounded.rem_euclid(size), v),
+        EdgeMode::Clamp if unbounded < 0 => (0, 0),
+        EdgeMode::Clamp if unbounded >= size => (size - 1, 0),
+        EdgeMode::Clamp => (unbounded, v),
+        EdgeMode::Bounce if size == 1 => (0, v),
+        EdgeMode::Bounce => {
+            // Bouncing between two walls is wrapping on a path twice as
+            // long, folded in half.
+            let period = 2 * (size - 1);
+            let folded = unbounded.rem_euclid(period);
+            if folded < size {
+                (folded, v)
+            } else {
+                (period - folded, -v)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RobotArena {
+    pub width: isize,
+    pub height: isize,
+    pub mode: EdgeMode,
+    pub robots: Vec<Robot>,
+}
+
+impl RobotArena {
+    pub fn from_string(input_str: &str, (width, height): (isize, isize), mode: EdgeMode) -> Self {
+        RobotArena {
+            width,
+            height,
+            mode,
+            robots: input_str.lines().map(Robot::from_string).collect(),
+        }
+    }
+
+    pub fn wh(&self) -> (isize, isize) {
+        (self.width, self.height)
+    }
+
+    pub fn tick_n(&mut self, n: isize) {
+        let (w, h, mode) = (self.width, self.height, self.mode);
+        for r in self.robots.iter_mut() {
+            let (x, vx) = move_on_axis(r.p.x, r.v.x, n, w, mode);
+            let (y, vy) = move_on_axis(r.p.y, r.v.y, n, h, mode);
+            r.p = Point::new(x, y);
+            r.v = Point::new(vx, vy);
+        }
+    }
+
+    pub fn positions(&self) -> Vec<Point> {
+        self.robots.iter().map(|r| r.p).collect()
+    }
+
+    // Number of robots on every cell.
+    pub fn occupancy(&self) -> Map<usize> {
+        let mut map = Map::from_size_default(self.width as usize, self.height as usize);
+        for r in &self.robots {
+            map[r.p] += 1;
+        }
+        map
+    }
+    pub fn occupied(&self) -> Map<bool> {
+        let mut map = Map::from_size_value(self.width as usize, self.height as usize, false);
+        for r in &self.robots {
+            map[r.p] = true;
+        }
+        map
+    }
+    // Cells hosting more than one robot, with their robot count.
+    pub fn collisions(&self) -> Vec<(Point, usize)> {
+        self.occupancy()
+            .enumerate()
+            .filter(|(_, n)| **n > 1)
+            .map(|(p, n)| (p, *n))
+            .collect()
+    }
+
+    // Robot count of each of the `nx` x `ny` regions the arena is split
+    // into, row by row. Robots on a row or column straddling two regions
+    // are not counted.
+    pub fn region_counts(&self, (nx, ny): (isize, isize)) -> Vec<usize> {
+        let mut counts = vec![0; (nx * ny) as usize];
+        for r in &self.robots {
+            if let (Some(rx), Some(ry)) = (
+                region_of(r.p.x, self.width, nx),
+                region_of(r.p.y, self.height, ny),
+            ) {
+                counts[(ry * nx + rx) as usize] += 1;
+            }
+        }
+        counts
+    }
+    pub fn safety_factor(&self, split: (isize, isize)) -> usize {
+        self.region_counts(split).iter().product()
+    }
+}
+
+fn region_of(coord: isize, size: isize, parts: isize) -> Option<isize> {
+    let straddles = (1..parts).any(|k| coord * parts < k * size && k * size < (coord + 1) * parts);
+    (!straddles).then_some(coord * parts / size)
+}
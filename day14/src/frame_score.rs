@@ -0,0 +1,136 @@
+use common_libs::map::{Map, Point};
+use itertools::Itertools;
+
+// Side of the square blocks robots are bucketed into to compute the entropy.
+const ENTROPY_BLOCK: usize = 10;
+// Relative spread under which a signal is taken as constant: rounding alone
+// gives a constant signal a tiny non-zero deviation.
+const MIN_SPREAD: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameStats {
+    pub variance: (f64, f64),
+    pub entropy: f64,
+    pub largest_cluster: usize,
+    pub longest_run: usize,
+}
+
+impl FrameStats {
+    pub fn new(positions: &[Point], (w, h): (isize, isize)) -> FrameStats {
+        let mut occupancy = Map::from_size_value(w as usize, h as usize, false);
+        for p in positions {
+            occupancy[p] = true;
+        }
+        FrameStats {
+            variance: (
+                variance(positions.iter().map(|p| p.x as f64)),
+                variance(positions.iter().map(|p| p.y as f64)),
+            ),
+            entropy: block_entropy(positions),
+            largest_cluster: largest_cluster(&occupancy),
+            longest_run: longest_run(&occupancy),
+        }
+    }
+
+    // Every signal is oriented so that "more ordered" means "higher".
+    fn signals(&self) -> [f64; 5] {
+        [
+            -self.variance.0,
+            -self.variance.1,
+            -self.entropy,
+            self.largest_cluster as f64,
+            self.longest_run as f64,
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candidate {
+    pub tick: usize,
+    pub score: f64,
+    pub stats: FrameStats,
+}
+
+// The score of a tick is the sum of the z-scores of its signals, so the most
+// anomalous (i.e. ordered) frame gets the highest one. Signals that do not
+// change from one frame to the next are left out. Returns every tick, best
+// candidate first, ties in tick order.
+pub fn rank_frames(frames: Vec<FrameStats>) -> Vec<Candidate> {
+    let signals = frames.iter().map(FrameStats::signals).collect_vec();
+    let n = signals.len() as f64;
+    let (mut mean, mut std_dev) = ([0f64; 5], [0f64; 5]);
+    for i in 0..5 {
+        mean[i] = signals.iter().map(|s| s[i]).sum::<f64>() / n;
+        std_dev[i] = variance(signals.iter().map(|s| s[i])).sqrt();
+    }
+    let varies = |i: usize| std_dev[i] > MIN_SPREAD * mean[i].abs().max(1.0);
+    frames
+        .into_iter()
+        .zip(signals)
+        .enumerate()
+        .map(|(tick, (stats, s))| Candidate {
+            tick,
+            score: (0..5)
+                .filter(|&i| varies(i))
+                .map(|i| (s[i] - mean[i]) / std_dev[i])
+                .sum(),
+            stats,
+        })
+        .sorted_by(|a, b| b.score.total_cmp(&a.score))
+        .collect()
+}
+
+fn variance(values: impl Iterator<Item = f64> + Clone) -> f64 {
+    let n = values.clone().count() as f64;
+    if n == 0.0 {
+        return 0.0;
+    }
+    let mean = values.clone().sum::<f64>() / n;
+    values.map(|v| (v - mean) * (v - mean)).sum::<f64>() / n
+}
+
+fn block_entropy(positions: &[Point]) -> f64 {
+    let n = positions.len() as f64;
+    positions
+        .iter()
+        .counts_by(|p| (p.x as usize / ENTROPY_BLOCK, p.y as usize / ENTROPY_BLOCK))
+        .values()
+        .map(|&c| {
+            let p = c as f64 / n;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn largest_cluster(occupancy: &Map<bool>) -> usize {
+    let mut labelled = Map::from_size_value(occupancy.width(), occupancy.height(), false);
+    let mut largest = 0;
+    for start in occupancy.find_all_positions(&true) {
+        if labelled[start] {
+            continue;
+        }
+        labelled[start] = true;
+        let mut stack = vec![start];
+        let mut size = 0;
+        while let Some(p) = stack.pop() {
+            size += 1;
+            for n in p.ortho_neighbours() {
+                if occupancy.is_pos_inside(&n) && occupancy[n] && !labelled[n] {
+                    labelled[n] = true;
+                    stack.push(n);
+                }
+            }
+        }
+        largest = largest.max(size);
+    }
+    largest
+}
+
+fn longest_run(occupancy: &Map<bool>) -> usize {
+    occupancy
+        .iter_rows()
+        .flat_map(|row| row.iter().dedup_with_count().filter(|(_, c)| **c))
+        .map(|(count, _)| count)
+        .max()
+        .unwrap_or(0)
+}
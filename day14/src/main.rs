@@ -1,98 +1,135 @@
-use common_libs::map::Point;
+mod arena;
+mod frame_score;
+
+use arena::{EdgeMode, RobotArena};
+use common_libs::cli::Args;
+use common_libs::export::Exporter;
+use common_libs::viz::{Frame, PlayMode, Player};
+use frame_score::{rank_frames, Candidate, FrameStats};
 use itertools::Itertools;
-use std::collections::HashMap;
 
-struct Robot {
-    pub p: Point,
-    v: Point,
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct ArenaConfig {
     wh: (isize, isize),
+    ticks: isize,
+    mode: EdgeMode,
 }
 
-impl Robot {
-    pub fn from_string_wh(input_str: &str, wh: (isize, isize)) -> Robot {
-        let (p0, v) = input_str
-            .split_whitespace()
-            .map(|x| x.split_once('=').unwrap().1)
-            .map(Point::from_string)
-            .collect_tuple()
-            .unwrap();
-        Robot { p: p0, v, wh }
-    }
-    pub fn tick_n(&mut self, n: isize) {
-        self.p = self.p + n * self.v;
-        self.p.x = self.p.x.rem_euclid(self.wh.0);
-        self.p.y = self.p.y.rem_euclid(self.wh.1);
+impl Default for ArenaConfig {
+    fn default() -> Self {
+        ArenaConfig {
+            wh: (101, 103),
+            ticks: 100,
+            mode: EdgeMode::Wrap,
+        }
     }
+}
 
-    fn is_in_middle(&self) -> bool {
-        let (w, h) = self.wh;
-        let (x, y) = (self.p.x, self.p.y);
-        (w % 2 != 0 && x == w / 2) || (h % 2 != 0 && y == h / 2)
+impl ArenaConfig {
+    // The arena needs at least one cell.
+    fn from_args(args: &Args) -> Result<ArenaConfig, String> {
+        let default = ArenaConfig::default();
+        let size = |name: &str, default: isize| match args.value_or(name, default) {
+            size if size > 0 => Ok(size),
+            size => Err(format!("Invalid {}: {}", &name[2..], size)),
+        };
+        Ok(ArenaConfig {
+            wh: (
+                size("--width", default.wh.0)?,
+                size("--height", default.wh.1)?,
+            ),
+            ticks: args.value_or("--ticks", default.ticks),
+            mode: args.value_or("--mode", default.mode),
+        })
     }
-    fn get_quadrant(&self) -> isize {
-        let (w, h) = self.wh;
-        let (x, y) = (self.p.x, self.p.y);
-        (y < h / 2) as isize + 2 * (x < w / 2) as isize
+    fn arena(&self, input_str: &str) -> RobotArena {
+        RobotArena::from_string(input_str, self.wh, self.mode)
     }
 }
 
-fn part1_wh(input_str: &String, wh: (isize, isize)) -> usize {
-    let time = 100;
-    let robots = input_str.lines().map(|s| Robot::from_string_wh(s, wh));
-    let quadrant_count: HashMap<isize, usize> = robots
-        .map(|mut r| {
-            r.tick_n(time);
-            r
-        })
-        .filter(|r| !r.is_in_middle())
-        .map(|r| r.get_quadrant())
-        .fold(HashMap::new(), |mut acc, q| {
-            *acc.entry(q).or_default() += 1;
-            acc
-        });
-    quadrant_count.values().fold(1, |score, n| score * n)
+// The arena after `config.ticks` ticks.
+fn simulate(input_str: &str, config: &ArenaConfig) -> RobotArena {
+    let mut arena = config.arena(input_str);
+    arena.tick_n(config.ticks);
+    arena
+}
+fn part1_config(input_str: &str, config: &ArenaConfig) -> usize {
+    simulate(input_str, config).safety_factor((2, 2))
 }
 fn part1(input_str: &String) -> usize {
-    part1_wh(input_str, (101, 103))
+    part1_config(input_str, &ArenaConfig::default())
 }
 
-fn get_as_image(robots: &Vec<Robot>, (w, h): (isize, isize)) -> image::GrayImage {
-    let mut buf = image::GrayImage::new(w as u32, h as u32);
-    for r in robots {
-        buf.put_pixel(r.p.x as u32, r.p.y as u32, image::Luma([255]));
+fn robot_palette(cell: &bool) -> [u8; 3] {
+    if *cell {
+        [255, 255, 255]
+    } else {
+        [0, 0, 0]
     }
-    buf
 }
 #[allow(dead_code)]
-fn part2_full_simulation(input_str: &String) {
-    let wh = (101, 103);
-    let mut robots = input_str
-        .lines()
-        .map(|s| Robot::from_string_wh(s, wh))
-        .collect_vec();
+fn part2_full_simulation(input_str: &String, config: &ArenaConfig) {
+    let mut arena = config.arena(input_str);
+    let (w, h) = arena.wh();
+    let frames = (0..=w * h).map(|_| {
+        let map = arena.occupied();
+        arena.tick_n(1);
+        map
+    });
+    Exporter::new(robot_palette)
+        .with_scale(2)
+        .with_frame_numbers(true)
+        .save_animation("frames/simulation.gif", frames)
+        .expect("Unable to save the simulation");
+}
+
+fn part2_single_frame(input_str: &String, config: &ArenaConfig, time: isize) {
+    let mut arena = config.arena(input_str);
+    arena.tick_n(time);
 
-    for t in 0..=wh.0 * wh.1 {
-        get_as_image(&robots, wh)
-            .save(format!("frames/frame-{}.png", t))
-            .unwrap();
-        robots.iter_mut().for_each(|r| r.tick_n(1));
+    Exporter::new(robot_palette)
+        .save_frame(format!("frames/frame-{}.png", time), &arena.occupied())
+        .expect("Unable to save the frame");
+}
+
+// The arena at every tick from 0 to `config.ticks`.
+fn play<W: std::io::Write>(input_str: &str, config: &ArenaConfig, player: &mut Player<W>) {
+    let mut arena = config.arena(input_str);
+    for tick in 0..=config.ticks {
+        let frame = Frame::from_map(&arena.occupied()).with_caption(format!("tick {}", tick));
+        player.show(&frame).expect("Unable to show frame");
+        arena.tick_n(1);
     }
 }
 
-fn part2_single_frame(input_str: &String, time: isize) {
-    let wh = (101, 103);
-    let robots = input_str
-        .lines()
-        .map(|s| Robot::from_string_wh(s, wh))
-        .map(|mut r| {
-            r.tick_n(time);
-            r
+// Every tick of a whole period (when wrapping), most anomalous first.
+fn part2_candidates(input_str: &str, config: &ArenaConfig) -> Vec<Candidate> {
+    let mut arena = config.arena(input_str);
+    let (w, h) = arena.wh();
+    let frames = (0..w * h)
+        .map(|_| {
+            let stats = FrameStats::new(&arena.positions(), arena.wh());
+            arena.tick_n(1);
+            stats
         })
         .collect_vec();
+    rank_frames(frames)
+}
 
-    get_as_image(&robots, wh)
-        .save(format!("frames/frame-{}.png", time))
-        .unwrap();
+fn print_report(candidates: &[Candidate], k: usize) {
+    println!("tick\tscore\tvar_x\tvar_y\tentropy\tcluster\trun");
+    for c in candidates.iter().take(k) {
+        println!(
+            "{}\t{:.2}\t{:.1}\t{:.1}\t{:.3}\t{}\t{}",
+            c.tick,
+            c.score,
+            c.stats.variance.0,
+            c.stats.variance.1,
+            c.stats.entropy,
+            c.stats.largest_cluster,
+            c.stats.longest_run
+        );
+    }
 }
 
 #[test]
@@ -112,12 +149,163 @@ fn demo() {
             p=9,5 v=-3,-3"
         .to_string();
 
-    assert_eq!(12, part1_wh(&input_str, (11, 7)));
+    let config = ArenaConfig {
+        wh: (11, 7),
+        ..ArenaConfig::default()
+    };
+    assert_eq!(12, part1_config(&input_str, &config));
+}
+
+#[test]
+fn playback() {
+    let config = ArenaConfig {
+        wh: (3, 2),
+        ticks: 2,
+        ..ArenaConfig::default()
+    };
+    let mut player = Player::new(Vec::new(), Box::new(std::io::empty()), PlayMode::Headless);
+    play("p=0,0 v=1,1", &config, &mut player);
+    assert_eq!(3, player.frames_shown());
+    assert_eq!(
+        "tick 0\n#..\n...\n\ntick 1\n...\n.#.\n\ntick 2\n..#\n...\n\n",
+        String::from_utf8(player.into_inner()).unwrap()
+    );
+}
+
+#[test]
+fn config_args() {
+    let parse =
+        |line: &str| ArenaConfig::from_args(&Args::new(line.split_whitespace().map(String::from)));
+    assert_eq!(
+        Ok(ArenaConfig {
+            wh: (11, 7),
+            ticks: 5,
+            mode: EdgeMode::Bounce,
+        }),
+        parse("--width 11 --height=7 --ticks 5 --mode bounce")
+    );
+    assert_eq!(Ok(ArenaConfig::default()), parse(""));
+    assert_eq!(
+        Err("Invalid width: 0".to_string()),
+        parse("--mode bounce --width 0")
+    );
+    assert_eq!(Err("Invalid height: -3".to_string()), parse("--height=-3"));
+}
+
+#[test]
+fn edge_modes() {
+    use common_libs::map::Point;
+    let input_str = "p=2,4 v=2,-3";
+    let robot_after = |mode, ticks| {
+        let mut arena = RobotArena::from_string(input_str, (11, 7), mode);
+        arena.tick_n(ticks);
+        arena.robots[0]
+    };
+    assert_eq!(Point::new(1, 3), robot_after(EdgeMode::Wrap, 5).p);
+    let clamped = robot_after(EdgeMode::Clamp, 5);
+    assert_eq!(
+        (Point::new(10, 0), Point::new(0, 0)),
+        (clamped.p, clamped.v)
+    );
+    // x: 2 4 6 8 10 8, y: 4 1 2 5 4 1
+    let bounced = robot_after(EdgeMode::Bounce, 5);
+    assert_eq!(
+        (Point::new(8, 1), Point::new(-2, -3)),
+        (bounced.p, bounced.v)
+    );
+    let mut arena = RobotArena::from_string(input_str, (11, 7), EdgeMode::Bounce);
+    (0..5).for_each(|_| arena.tick_n(1));
+    assert_eq!(bounced, arena.robots[0]);
+}
+
+#[test]
+fn collisions_and_regions() {
+    use common_libs::map::Point;
+    let input_str = "p=0,0 v=1,0\np=1,0 v=0,0\np=2,2 v=0,0\np=4,4 v=0,0\np=4,1 v=0,0";
+    let mut arena = RobotArena::from_string(input_str, (5, 5), EdgeMode::Wrap);
+    arena.tick_n(1);
+    assert_eq!(vec![(Point::new(1, 0), 2)], arena.collisions());
+    assert_eq!(vec![2, 1, 0, 1], arena.region_counts((2, 2)));
+    // with 3 regions, columns and rows 1 and 3 straddle two of them
+    assert_eq!(vec![0, 0, 0, 0, 1, 0, 0, 0, 1], arena.region_counts((3, 3)));
+}
+
+#[test]
+fn frame_ranking() {
+    use common_libs::map::Point;
+    let scattered = [
+        (0, 0),
+        (19, 0),
+        (0, 19),
+        (19, 19),
+        (10, 5),
+        (5, 10),
+        (14, 14),
+        (3, 16),
+        (16, 3),
+    ];
+    let clustered = [
+        (8, 8),
+        (9, 8),
+        (10, 8),
+        (8, 9),
+        (9, 9),
+        (10, 9),
+        (8, 10),
+        (9, 10),
+        (10, 10),
+    ];
+    let shifted = scattered.map(|(x, y)| ((x + 7) % 20, (y + 3) % 20));
+    let frames = [scattered, clustered, shifted]
+        .iter()
+        .map(|frame| {
+            let positions = frame.iter().map(|(x, y)| Point::new(*x, *y)).collect_vec();
+            FrameStats::new(&positions, (20, 20))
+        })
+        .collect_vec();
+    let ranked = rank_frames(frames);
+    assert_eq!(1, ranked[0].tick);
+    assert_eq!(9, ranked[0].stats.largest_cluster);
+    assert_eq!(3, ranked[0].stats.longest_run);
+
+    // Only the cluster size changes, the constant signals must not add any
+    // rounding noise to the scores.
+    let stats = |largest_cluster| FrameStats {
+        variance: (0.1, 0.1),
+        entropy: 0.1,
+        largest_cluster,
+        longest_run: 1,
+    };
+    let ranked = rank_frames(vec![stats(1), stats(3), stats(2)]);
+    assert_eq!(vec![1, 2, 0], ranked.iter().map(|c| c.tick).collect_vec());
+    assert_eq!(1.5f64.sqrt(), ranked[0].score);
+    assert_eq!(0.0, ranked[1].score);
+    let ranked = rank_frames(vec![stats(4); 3]);
+    assert_eq!(vec![0, 1, 2], ranked.iter().map(|c| c.tick).collect_vec());
+    assert!(ranked.iter().all(|c| c.score == 0.0));
 }
 
 fn main() {
     let input_filename = "input";
     let input_str = std::fs::read_to_string(input_filename).expect("Unable to read file");
+    let config = ArenaConfig::from_args(&Args::from_env()).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1)
+    });
+    if let Some(mode) = PlayMode::from_args(std::env::args()).unwrap_or_else(|e| panic!("{}", e)) {
+        play(&input_str, &config, &mut Player::terminal(mode));
+        return;
+    }
+    if config != ArenaConfig::default() {
+        let arena = simulate(&input_str, &config);
+        println!("{}", arena.safety_factor((2, 2)));
+        println!("collisions: {}", arena.collisions().len());
+        return;
+    }
+
     assert_eq!(231782040, part1(&input_str));
-    part2_single_frame(&input_str, 6475);
+    let candidates = part2_candidates(&input_str, &config);
+    print_report(&candidates, 5);
+    assert_eq!(6475, candidates[0].tick);
+    part2_single_frame(&input_str, &config, candidates[0].tick as isize);
 }
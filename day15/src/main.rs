@@ -1,14 +1,15 @@
+use common_libs::direction::Direction;
 use common_libs::map::{Map, Point};
-mod direction;
+use common_libs::viz::{Frame, FrameObserver, PlayMode, Player};
 mod tile;
-use direction::Direction;
 use itertools::Itertools;
 use tile::Tile;
 
 trait Day15Map {
     fn widen(&self) -> Map<Tile>;
     fn get_gps_score(&self) -> isize;
-    fn execute_instructions(&self, inst_list: Vec<Direction>) -> Map<Tile>;
+    fn execute_instructions(&self, inst_list: Vec<Direction>, observer: FrameObserver)
+        -> Map<Tile>;
 }
 
 impl Day15Map for Map<Tile> {
@@ -25,20 +26,38 @@ impl Day15Map for Map<Tile> {
             .filter(|(_, c)| **c == Tile::Box || **c == Tile::LeftBox)
             .fold(0, |acc, (p, _)| acc + 100 * p.y + p.x)
     }
-    fn execute_instructions(&self, inst_list: Vec<Direction>) -> Map<Tile> {
+    fn execute_instructions(
+        &self,
+        inst_list: Vec<Direction>,
+        mut observer: FrameObserver,
+    ) -> Map<Tile> {
         let mut map = self.clone();
         let start_robot_pos = map.position(Tile::Robot).unwrap(); // TODO get this from process_input, maybe?
-        inst_list.iter().fold(start_robot_pos, |robot_pos, inst| {
-            robot_pos
-                + push(&mut map, robot_pos, *inst)
-                    .then_some(inst.to_point())
-                    .unwrap_or_default()
-        });
+        if let Some(observer) = observer.as_mut() {
+            observer(&Frame::from_map(&map).with_caption("start"));
+        }
+        inst_list
+            .iter()
+            .enumerate()
+            .fold(start_robot_pos, |robot_pos, (i, inst)| {
+                let new_robot_pos = robot_pos
+                    + push(&mut map, robot_pos, *inst)
+                        .then_some(inst.to_point())
+                        .unwrap_or_default();
+                if let Some(observer) = observer.as_mut() {
+                    observer(&Frame::from_map(&map).with_caption(format!(
+                        "move {}: {:?}",
+                        i + 1,
+                        inst
+                    )));
+                }
+                new_robot_pos
+            });
         map
     }
 }
 
-fn process_input(input_str: &String) -> (Map<Tile>, Vec<Direction>) {
+fn process_input(input_str: &str) -> (Map<Tile>, Vec<Direction>) {
     let (map_input, instruction_input) = input_str.split_once("\n\n").unwrap();
     let instruction_input = instruction_input
         .lines()
@@ -93,12 +112,23 @@ fn push_action(m: &mut Map<Tile>, pos: Point, dir: Direction) {
 
 fn part1(input_str: &String) -> isize {
     let (map, inst_list) = process_input(&input_str);
-    map.execute_instructions(inst_list).get_gps_score()
+    map.execute_instructions(inst_list, None).get_gps_score()
 }
 
 fn part2(input_str: &String) -> isize {
     let (map, inst_list) = process_input(&input_str);
-    map.widen().execute_instructions(inst_list).get_gps_score()
+    map.widen()
+        .execute_instructions(inst_list, None)
+        .get_gps_score()
+}
+
+fn visualize(input_str: &str, mode: PlayMode) {
+    let (map, inst_list) = process_input(input_str);
+    let mut player = Player::terminal(mode);
+    map.widen().execute_instructions(
+        inst_list,
+        Some(&mut |frame| player.show(frame).expect("Unable to show frame")),
+    );
 }
 
 #[test]
@@ -133,6 +163,10 @@ fn demo() {
 fn main() {
     let input_filename = "input";
     let input_str = std::fs::read_to_string(input_filename).expect("Unable to read file");
+    if let Some(mode) = PlayMode::from_args(std::env::args()).unwrap_or_else(|e| panic!("{}", e)) {
+        visualize(&input_str, mode);
+        return;
+    }
     assert_eq!(1552463, part1(&input_str));
     assert_eq!(1554058, part2(&input_str));
 }
@@ -1,6 +1,7 @@
 use common_libs::{
     map::Point,
     point::{LX_VEC, RX_VEC},
+    viz::{Cell, Color},
 };
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
@@ -41,3 +42,24 @@ impl Tile {
         }
     }
 }
+
+impl Cell for Tile {
+    fn glyph(&self) -> char {
+        match self {
+            Tile::Wall => '#',
+            Tile::Empty => '.',
+            Tile::Box => 'O',
+            Tile::Robot => '@',
+            Tile::LeftBox => '[',
+            Tile::RightBox => ']',
+        }
+    }
+    fn color(&self) -> Color {
+        match self {
+            Tile::Wall => Color::Blue,
+            Tile::Empty => Color::Default,
+            Tile::Box | Tile::LeftBox | Tile::RightBox => Color::Yellow,
+            Tile::Robot => Color::Red,
+        }
+    }
+}